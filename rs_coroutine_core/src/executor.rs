@@ -1,6 +1,12 @@
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Handler invoked with the message of an uncaught coroutine panic
+pub type CoroutineExceptionHandler = Arc<dyn Fn(&str) + Send + Sync>;
+
+static EXCEPTION_HANDLER: RwLock<Option<CoroutineExceptionHandler>> = RwLock::new(None);
 
 /// Minimal executor trait for spawning futures
 pub trait Executor: Send + Sync + 'static {
@@ -11,17 +17,53 @@ pub trait Executor: Send + Sync + 'static {
 #[derive(Clone)]
 pub struct Dispatcher {
     inner: Arc<dyn Executor>,
+    metrics: Arc<DispatcherMetrics>,
+}
+
+/// Task counters shared by all clones of a Dispatcher
+#[derive(Default)]
+struct DispatcherMetrics {
+    active: AtomicUsize,
+    spawned: AtomicU64,
+}
+
+/// Marks a spawned task as finished when the task completes or is dropped
+struct ActiveTask(Arc<DispatcherMetrics>);
+
+impl Drop for ActiveTask {
+    fn drop(&mut self) {
+        self.0.active.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 impl Dispatcher {
     /// Create a new Dispatcher from an Executor
     pub fn new(inner: Arc<dyn Executor>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            metrics: Arc::default(),
+        }
     }
 
     /// Spawn a future on this dispatcher
     pub fn spawn(&self, fut: impl Future<Output = ()> + Send + 'static) {
-        self.inner.spawn(Box::pin(fut));
+        self.metrics.spawned.fetch_add(1, Ordering::SeqCst);
+        self.metrics.active.fetch_add(1, Ordering::SeqCst);
+        let task = ActiveTask(Arc::clone(&self.metrics));
+        self.inner.spawn(Box::pin(async move {
+            let _task = task;
+            fut.await;
+        }));
+    }
+
+    /// Number of tasks spawned on this dispatcher that have not finished yet
+    pub fn active_tasks(&self) -> usize {
+        self.metrics.active.load(Ordering::SeqCst)
+    }
+
+    /// Total number of tasks ever spawned on this dispatcher
+    pub fn total_spawned(&self) -> u64 {
+        self.metrics.spawned.load(Ordering::SeqCst)
     }
 }
 
@@ -38,14 +80,52 @@ impl Executor for TokioExecutor {
 pub struct Dispatchers;
 
 impl Dispatchers {
-    /// Main/Default dispatcher using Tokio runtime
+    /// Main/Default dispatcher using Tokio runtime.
+    /// Every call returns the same dispatcher, so its task metrics cover all users.
     pub fn main() -> Dispatcher {
-        Dispatcher::new(Arc::new(TokioExecutor))
+        static MAIN: OnceLock<Dispatcher> = OnceLock::new();
+        MAIN.get_or_init(|| Dispatcher::new(Arc::new(TokioExecutor)))
+            .clone()
     }
 
-    /// IO dispatcher for blocking IO operations
+    /// IO dispatcher for blocking IO operations.
+    /// Every call returns the same dispatcher, so its task metrics cover all users.
     pub fn io() -> Dispatcher {
-        Dispatcher::new(Arc::new(TokioExecutor))
+        static IO: OnceLock<Dispatcher> = OnceLock::new();
+        IO.get_or_init(|| Dispatcher::new(Arc::new(TokioExecutor)))
+            .clone()
+    }
+
+    /// Install a global handler for panics in coroutines started with `launch`.
+    /// Replaces any previously installed handler.
+    pub fn set_exception_handler<F>(handler: F)
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        *EXCEPTION_HANDLER.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(handler));
+    }
+
+    /// Remove the global exception handler, restoring the default: logged
+    /// through the `log` crate with the `log` feature, otherwise ignored
+    pub fn clear_exception_handler() {
+        *EXCEPTION_HANDLER.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// Report an uncaught coroutine panic to the global exception handler
+pub(crate) fn handle_uncaught_panic(message: &str) {
+    let handler = EXCEPTION_HANDLER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    match handler {
+        Some(handler) => handler(message),
+        #[cfg(feature = "log")]
+        None => {
+            log::error!(target: "rs_coroutine_core", "Uncaught panic in coroutine: {}", message)
+        }
+        #[cfg(not(feature = "log"))]
+        None => {}
     }
 }
 
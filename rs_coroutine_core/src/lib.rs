@@ -6,7 +6,10 @@ pub mod job;
 pub mod scope;
 pub mod suspending;
 
-pub use executor::{Dispatcher, Dispatchers, Executor, TokioExecutor};
+pub use executor::{CoroutineExceptionHandler, Dispatcher, Dispatchers, Executor, TokioExecutor};
 pub use job::{CancelToken, JobHandle};
-pub use scope::{get_current_scope, with_current_scope, CoroutineScope, Deferred, CURRENT_SCOPE};
+pub use scope::{
+    get_current_scope, panic_message, with_current_scope, with_non_cancellable, yield_now,
+    CoroutineScope, Deferred, TaskError, CURRENT_SCOPE,
+};
 pub use suspending::Suspending;
@@ -1,8 +1,11 @@
-use crate::executor::Dispatcher;
+use crate::executor::{handle_uncaught_panic, Dispatcher};
 use crate::job::{CancelToken, JobHandle};
+use futures::FutureExt;
+use std::any::Any;
 use std::future::Future;
-use std::sync::Arc;
-use tokio::sync::oneshot;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::{oneshot, Notify};
 
 tokio::task_local! {
     pub static CURRENT_SCOPE: Arc<CoroutineScope>;
@@ -14,6 +17,36 @@ pub struct CoroutineScope {
     pub dispatcher: Dispatcher,
     pub job: JobHandle,
     pub cancel_token: CancelToken,
+    children: Arc<Children>,
+}
+
+/// Registry of the jobs started by a scope
+#[derive(Default)]
+struct Children {
+    /// An entry is alive while its task runs
+    running: Mutex<Vec<Weak<RunningChild>>>,
+    /// Notified whenever a child task finishes
+    finished: Notify,
+}
+
+impl Children {
+    fn active(&self) -> Vec<Arc<RunningChild>> {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        running.retain(|child| child.strong_count() > 0);
+        running.iter().filter_map(Weak::upgrade).collect()
+    }
+}
+
+/// Held by a child's task for as long as it runs
+struct RunningChild {
+    job: JobHandle,
+    children: Arc<Children>,
+}
+
+impl Drop for RunningChild {
+    fn drop(&mut self) {
+        self.children.finished.notify_waiters();
+    }
 }
 
 impl CoroutineScope {
@@ -23,10 +56,15 @@ impl CoroutineScope {
             dispatcher,
             job: JobHandle::new(),
             cancel_token: CancelToken::new(),
+            children: Arc::default(),
         }
     }
 
     /// Launch a new coroutine in this scope
+    ///
+    /// A panic in the coroutine is reported to the global exception handler
+    /// (see `Dispatchers::set_exception_handler`). Cancelling the returned
+    /// job stops the coroutine at its next suspension point.
     pub fn launch<F>(&self, fut: F) -> JobHandle
     where
         F: Future<Output = ()> + Send + 'static,
@@ -36,14 +74,21 @@ impl CoroutineScope {
         let job = self.job.new_child();
         let cancel_token = self.cancel_token.clone();
 
-        let job_clone = job.clone();
+        let running = self.track_child(&job);
         dispatcher.spawn(async move {
             CURRENT_SCOPE
                 .scope(scope.clone(), async move {
                     if !cancel_token.is_cancelled() {
-                        fut.await;
+                        tokio::select! {
+                            result = AssertUnwindSafe(fut).catch_unwind() => {
+                                if let Err(payload) = result {
+                                    handle_uncaught_panic(&panic_message(payload.as_ref()));
+                                }
+                            }
+                            _ = running.job.cancel_token().cancelled() => {}
+                        }
                     }
-                    job_clone.complete();
+                    running.job.complete();
                 })
                 .await;
         });
@@ -51,7 +96,25 @@ impl CoroutineScope {
         job
     }
 
+    /// Launch a new coroutine in this scope, reporting a panic to `on_panic`
+    /// with the panic message instead of letting it die silently
+    pub fn launch_catching<F, H>(&self, fut: F, on_panic: H) -> JobHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+        H: Fn(String) + Send + Sync + 'static,
+    {
+        self.launch(async move {
+            if let Err(payload) = AssertUnwindSafe(fut).catch_unwind().await {
+                on_panic(panic_message(payload.as_ref()));
+            }
+        })
+    }
+
     /// Switch to a different dispatcher for the given future
+    ///
+    /// The block is tracked as a child job but is not aborted by
+    /// `cancel_children`, since the caller is waiting for its value; it can
+    /// check `get_current_scope().job.is_cancelled()` to stop early.
     pub async fn with_dispatcher<F, T>(&self, dispatcher: Dispatcher, fut: F) -> T
     where
         F: Future<Output = T> + Send + 'static,
@@ -62,18 +125,61 @@ impl CoroutineScope {
             dispatcher: dispatcher.clone(),
             job: self.job.new_child(),
             cancel_token: self.cancel_token.child(),
+            children: Arc::default(),
         });
 
+        let running = self.track_child(&child_scope.job);
         dispatcher.spawn(async move {
             let res = CURRENT_SCOPE.scope(child_scope, fut).await;
             let _ = tx.send(res);
+            drop(running);
         });
 
         rx.await.expect("dispatcher dropped future")
     }
 
     /// Async task that returns a Deferred
+    ///
+    /// Cancelling the task, through its job or `cancel_children`, stops it at
+    /// its next suspension point; `Deferred::try_await_result` then reports
+    /// `TaskError::Cancelled`.
     pub fn async_task<F, T>(&self, dispatcher: Dispatcher, fut: F) -> Deferred<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.spawn_deferred(dispatcher, fut, None)
+    }
+
+    /// Like `async_task`, but a panic in `fut` is caught and delivered as
+    /// `Err(TaskError::Panicked)` instead of failing `await_result`, and
+    /// cancellation is delivered as `Err(TaskError::Cancelled)`
+    pub fn try_async_task<F, T>(
+        &self,
+        dispatcher: Dispatcher,
+        fut: F,
+    ) -> Deferred<Result<T, TaskError>>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let fut = async move {
+            AssertUnwindSafe(fut)
+                .catch_unwind()
+                .await
+                .map_err(|payload| TaskError::Panicked(panic_message(payload.as_ref())))
+        };
+        self.spawn_deferred(dispatcher, fut, Some(Err(TaskError::Cancelled)))
+    }
+
+    /// Start `fut` as a cancellable child job. On cancellation the deferred
+    /// receives `on_cancel`, or nothing if it is `None`.
+    fn spawn_deferred<F, T>(
+        &self,
+        dispatcher: Dispatcher,
+        fut: F,
+        on_cancel: Option<T>,
+    ) -> Deferred<T>
     where
         F: Future<Output = T> + Send + 'static,
         T: Send + 'static,
@@ -83,17 +189,70 @@ impl CoroutineScope {
             dispatcher: dispatcher.clone(),
             job: self.job.new_child(),
             cancel_token: self.cancel_token.child(),
+            children: Arc::default(),
         });
         let job = child_scope.job.clone();
 
+        let running = self.track_child(&job);
         dispatcher.spawn(async move {
-            let res = CURRENT_SCOPE.scope(child_scope, fut).await;
-            let _ = tx.send(res);
+            tokio::select! {
+                res = CURRENT_SCOPE.scope(child_scope, fut) => {
+                    let _ = tx.send(res);
+                }
+                _ = running.job.cancel_token().cancelled() => {
+                    if let Some(value) = on_cancel {
+                        let _ = tx.send(value);
+                    }
+                }
+            }
         });
 
         Deferred { rx, job }
     }
 
+    /// Number of jobs started by `launch`, `async_task` or `with_dispatcher`
+    /// that are still running
+    pub fn active_children(&self) -> usize {
+        self.children.active().len()
+    }
+
+    /// Cancel every running child job without cancelling the scope itself
+    pub fn cancel_children(&self) {
+        for child in self.children.active() {
+            child.job.cancel();
+        }
+    }
+
+    /// Wait until every child job has finished, including children started
+    /// while waiting
+    pub async fn join_children(&self) {
+        loop {
+            // Created before the check so a child finishing in between is not missed
+            let finished = self.children.finished.notified();
+            if self.children.active().is_empty() {
+                return;
+            }
+            finished.await;
+        }
+    }
+
+    /// Register a child job. The returned entry is held by the child's task,
+    /// so it expires once the task finishes or is dropped.
+    fn track_child(&self, job: &JobHandle) -> Arc<RunningChild> {
+        let running = Arc::new(RunningChild {
+            job: job.clone(),
+            children: Arc::clone(&self.children),
+        });
+        let mut children = self
+            .children
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        children.retain(|child| child.strong_count() > 0);
+        children.push(Arc::downgrade(&running));
+        running
+    }
+
     /// Cancel this scope
     pub fn cancel(&self) {
         self.cancel_token.cancel();
@@ -106,6 +265,27 @@ impl CoroutineScope {
     }
 }
 
+/// Why a task started by `try_async_task` or awaited with
+/// `Deferred::try_await_result` produced no value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskError {
+    /// The task panicked with the given message
+    Panicked(String),
+    /// The task was cancelled before producing a value
+    Cancelled,
+}
+
+impl std::fmt::Display for TaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskError::Panicked(message) => write!(f, "Task panicked: {}", message),
+            TaskError::Cancelled => write!(f, "Task cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for TaskError {}
+
 /// A deferred value that can be awaited
 pub struct Deferred<T> {
     rx: oneshot::Receiver<T>,
@@ -114,16 +294,36 @@ pub struct Deferred<T> {
 
 impl<T> Deferred<T> {
     /// Await the deferred value
+    ///
+    /// Panics if the task was cancelled; use `try_await_result` when the task
+    /// may be cancelled.
     pub async fn await_result(self) -> T {
         self.rx.await.expect("task dropped")
     }
 
+    /// Await the deferred value, reporting `TaskError::Cancelled` if the task
+    /// was cancelled before producing it
+    pub async fn try_await_result(self) -> Result<T, TaskError> {
+        self.rx.await.map_err(|_| TaskError::Cancelled)
+    }
+
     /// Get the job handle
     pub fn job(&self) -> &JobHandle {
         &self.job
     }
 }
 
+/// Extract a readable message from a panic payload, as caught by `catch_unwind`
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Unknown panic".to_string()
+    }
+}
+
 /// Helper to access the current scope
 pub async fn with_current_scope<F, Fut, T>(f: F) -> T
 where
@@ -133,6 +333,46 @@ where
     CURRENT_SCOPE.with(|scope| f(scope)).await
 }
 
+/// Run cleanup work that cannot be cancelled, like Kotlin's `withContext(NonCancellable)`
+///
+/// The future runs inside a fresh scope whose job and cancel token are
+/// detached from the caller's scope, so cancellation of the caller is not
+/// observed by `get_current_scope().is_cancelled()` inside it.
+///
+/// # Example
+/// ```ignore
+/// with_non_cancellable(|| async {
+///     connection.close().await;
+/// })
+/// .await;
+/// ```
+pub async fn with_non_cancellable<F, Fut, T>(f: F) -> T
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = T>,
+{
+    let dispatcher = CURRENT_SCOPE
+        .try_with(|scope| scope.dispatcher.clone())
+        .unwrap_or_default();
+    let scope = Arc::new(CoroutineScope::new(dispatcher));
+    CURRENT_SCOPE.scope(scope, f()).await
+}
+
+/// Yield control back to the executor so other tasks can make progress
+///
+/// Useful inside tight loops that would otherwise monopolise a worker thread.
+///
+/// # Example
+/// ```ignore
+/// for item in items {
+///     process(item);
+///     yield_now().await;
+/// }
+/// ```
+pub async fn yield_now() {
+    tokio::task::yield_now().await
+}
+
 /// Helper to get a reference to the current scope (for macros)
 pub fn get_current_scope() -> Arc<CoroutineScope> {
     CURRENT_SCOPE.with(Arc::clone)
@@ -1,4 +1,6 @@
+use futures::FutureExt;
 use std::future::Future;
+use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
 use std::sync::Arc;
 
@@ -37,6 +39,53 @@ impl<T> Suspending<T> {
     }
 }
 
+impl<T> Suspending<T>
+where
+    T: Send + 'static,
+{
+    /// Combine two suspending blocks into one that runs both concurrently
+    /// and returns both results
+    ///
+    /// # Example
+    /// ```ignore
+    /// let both = fetch_user.zip(fetch_settings);
+    /// let (user, settings) = both.call().await;
+    /// ```
+    pub fn zip<U>(self, other: Suspending<U>) -> Suspending<(T, U)>
+    where
+        U: Send + 'static,
+    {
+        Suspending::new(move || {
+            let first = self.clone();
+            let second = other.clone();
+            Box::pin(async move { tokio::join!(first.call(), second.call()) })
+        })
+    }
+
+    /// Re-invoke the suspending block up to `times` more times if it panics.
+    /// The last panic is propagated once the retries are exhausted.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let fetch = fetch_config.retry(3);
+    /// ```
+    pub fn retry(self, times: usize) -> Suspending<T> {
+        Suspending::new(move || {
+            let block = self.clone();
+            Box::pin(async move {
+                let mut attempts = 0;
+                loop {
+                    match AssertUnwindSafe(block.call()).catch_unwind().await {
+                        Ok(value) => return value,
+                        Err(_) if attempts < times => attempts += 1,
+                        Err(panic) => std::panic::resume_unwind(panic),
+                    }
+                }
+            })
+        })
+    }
+}
+
 /// Macro to create a suspending block
 #[macro_export]
 macro_rules! suspend_block {
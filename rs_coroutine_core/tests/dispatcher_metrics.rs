@@ -0,0 +1,33 @@
+use rs_coroutine_core::{Dispatcher, Dispatchers, TokioExecutor};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+
+#[tokio::test]
+async fn active_tasks_rise_and_fall() {
+    let dispatcher = Dispatcher::new(Arc::new(TokioExecutor));
+    assert_eq!(dispatcher.active_tasks(), 0);
+
+    for delay in [10, 20, 30] {
+        dispatcher.spawn(async move {
+            sleep(Duration::from_millis(delay)).await;
+        });
+    }
+    assert_eq!(dispatcher.active_tasks(), 3);
+    assert_eq!(dispatcher.clone().total_spawned(), 3);
+
+    for _ in 0..100 {
+        if dispatcher.active_tasks() == 0 {
+            break;
+        }
+        sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(dispatcher.active_tasks(), 0);
+    assert_eq!(dispatcher.total_spawned(), 3);
+}
+
+#[tokio::test]
+async fn standard_dispatchers_share_metrics() {
+    let before = Dispatchers::io().total_spawned();
+    Dispatchers::io().spawn(async {});
+    assert!(Dispatchers::io().total_spawned() > before);
+}
@@ -0,0 +1,20 @@
+use rs_coroutine_core::{CoroutineScope, Dispatchers};
+use std::sync::{Arc, Mutex};
+
+#[tokio::test]
+async fn global_handler_receives_uncaught_panics() {
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let captured_clone = Arc::clone(&captured);
+    Dispatchers::set_exception_handler(move |message| {
+        captured_clone.lock().unwrap().push(message.to_string());
+    });
+
+    let scope = CoroutineScope::new(Dispatchers::main());
+    let job = scope.launch(async {
+        panic!("boom");
+    });
+    job.join().await;
+
+    Dispatchers::clear_exception_handler();
+    assert_eq!(*captured.lock().unwrap(), vec!["boom".to_string()]);
+}
@@ -1,7 +1,9 @@
-use rs_coroutine_core::{CoroutineScope, Dispatchers};
+use rs_coroutine_core::{
+    get_current_scope, with_non_cancellable, CoroutineScope, Dispatchers, TaskError,
+};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 use tokio::time::{sleep, Duration};
 
@@ -35,3 +37,173 @@ async fn cancellation_stops_future_progress() {
 
     assert!(!flag.load(Ordering::SeqCst));
 }
+
+#[tokio::test]
+async fn launch_catching_reports_panic_message() {
+    let scope = CoroutineScope::new(Dispatchers::main());
+    let reported = Arc::new(Mutex::new(None));
+    let reported_clone = Arc::clone(&reported);
+
+    let job = scope.launch_catching(
+        async {
+            panic!("coroutine failed");
+        },
+        move |message| {
+            *reported_clone.lock().unwrap() = Some(message);
+        },
+    );
+
+    job.join().await;
+    assert_eq!(
+        reported.lock().unwrap().as_deref(),
+        Some("coroutine failed")
+    );
+}
+
+#[tokio::test]
+async fn try_async_task_reports_panic_as_error() {
+    let scope = CoroutineScope::new(Dispatchers::main());
+
+    let failed = scope.try_async_task(Dispatchers::main(), async {
+        panic!("task failed");
+    });
+    let succeeded = scope.try_async_task(Dispatchers::main(), async { 42 });
+
+    assert_eq!(
+        failed.await_result().await,
+        Err::<(), _>(TaskError::Panicked("task failed".to_string()))
+    );
+    assert_eq!(succeeded.await_result().await, Ok(42));
+}
+
+#[tokio::test]
+async fn try_async_task_reports_cancellation_as_error() {
+    let scope = CoroutineScope::new(Dispatchers::main());
+
+    let deferred = scope.try_async_task(Dispatchers::main(), async {
+        sleep(Duration::from_secs(10)).await;
+        42
+    });
+    deferred.job().cancel();
+
+    assert_eq!(deferred.await_result().await, Err(TaskError::Cancelled));
+}
+
+#[tokio::test]
+async fn non_cancellable_cleanup_runs_after_cancel() {
+    let scope = CoroutineScope::new(Dispatchers::main());
+    let cleaned_up = Arc::new(AtomicBool::new(false));
+    let cleaned_up_clone = Arc::clone(&cleaned_up);
+    let (started_tx, started_rx) = tokio::sync::oneshot::channel::<()>();
+    let (cancelled_tx, cancelled_rx) = tokio::sync::oneshot::channel::<()>();
+
+    let job = scope.launch(async move {
+        started_tx.send(()).unwrap();
+        cancelled_rx.await.unwrap();
+        assert!(get_current_scope().is_cancelled());
+
+        let completed = with_non_cancellable(|| async {
+            sleep(Duration::from_millis(20)).await;
+            !get_current_scope().is_cancelled()
+        })
+        .await;
+        cleaned_up_clone.store(completed, Ordering::SeqCst);
+    });
+
+    started_rx.await.unwrap();
+    scope.cancel();
+    cancelled_tx.send(()).unwrap();
+    job.join().await;
+
+    assert!(cleaned_up.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn cancel_children_stops_active_children() {
+    let scope = CoroutineScope::new(Dispatchers::main());
+    let finished = Arc::new(AtomicBool::new(false));
+
+    let jobs: Vec<_> = (0..3)
+        .map(|_| {
+            let finished = Arc::clone(&finished);
+            scope.launch(async move {
+                sleep(Duration::from_secs(10)).await;
+                finished.store(true, Ordering::SeqCst);
+            })
+        })
+        .collect();
+    assert_eq!(scope.active_children(), 3);
+
+    scope.cancel_children();
+    for _ in 0..100 {
+        if scope.active_children() == 0 {
+            break;
+        }
+        sleep(Duration::from_millis(1)).await;
+    }
+
+    assert_eq!(scope.active_children(), 0);
+    assert!(jobs.iter().all(|job| job.is_cancelled()));
+    assert!(!finished.load(Ordering::SeqCst));
+    assert!(!scope.is_cancelled());
+}
+
+#[tokio::test]
+async fn join_children_waits_for_all_children() {
+    let scope = CoroutineScope::new(Dispatchers::main());
+    let finished = Arc::new(Mutex::new(Vec::new()));
+
+    for delay in [30, 10, 20] {
+        let finished = Arc::clone(&finished);
+        let inner_scope = scope.clone();
+        scope.launch(async move {
+            sleep(Duration::from_millis(delay)).await;
+            if delay == 10 {
+                // A child started while the parent is already joining
+                let finished = Arc::clone(&finished);
+                inner_scope.launch(async move {
+                    sleep(Duration::from_millis(40)).await;
+                    finished.lock().unwrap().push(50);
+                });
+            }
+            finished.lock().unwrap().push(delay);
+        });
+    }
+
+    tokio::time::timeout(Duration::from_secs(1), scope.join_children())
+        .await
+        .expect("children should finish");
+
+    assert_eq!(*finished.lock().unwrap(), vec![10, 20, 30, 50]);
+    assert_eq!(scope.active_children(), 0);
+}
+
+#[tokio::test]
+async fn cancel_children_leaves_callers_a_result() {
+    let scope = CoroutineScope::new(Dispatchers::main());
+    let (started_tx, started_rx) = tokio::sync::oneshot::channel::<()>();
+
+    let switching_scope = scope.clone();
+    let switched = tokio::spawn(async move {
+        switching_scope
+            .with_dispatcher(Dispatchers::main(), async move {
+                started_tx.send(()).unwrap();
+                while !get_current_scope().job.is_cancelled() {
+                    sleep(Duration::from_millis(1)).await;
+                }
+                "stopped early"
+            })
+            .await
+    });
+    let deferred = scope.async_task(Dispatchers::main(), async {
+        sleep(Duration::from_secs(10)).await;
+        42
+    });
+
+    started_rx.await.unwrap();
+    assert_eq!(scope.active_children(), 2);
+    scope.cancel_children();
+
+    assert_eq!(deferred.try_await_result().await, Err(TaskError::Cancelled));
+    assert_eq!(switched.await.unwrap(), "stopped early");
+}
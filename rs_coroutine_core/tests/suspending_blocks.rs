@@ -0,0 +1,44 @@
+use rs_coroutine_core::suspend_block;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::time::{sleep, Duration, Instant};
+
+#[tokio::test]
+async fn zip_runs_both_blocks_concurrently() {
+    let slow = suspend_block! {
+        sleep(Duration::from_millis(100)).await;
+        1
+    };
+    let slower = suspend_block! {
+        sleep(Duration::from_millis(150)).await;
+        "two"
+    };
+
+    let started = Instant::now();
+    let result = slow.zip(slower).call().await;
+    let elapsed = started.elapsed();
+
+    assert_eq!(result, (1, "two"));
+    assert!(elapsed >= Duration::from_millis(150));
+    assert!(elapsed < Duration::from_millis(240));
+}
+
+#[tokio::test]
+async fn retry_reinvokes_after_panic() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let attempts_clone = Arc::clone(&attempts);
+    let flaky = rs_coroutine_core::Suspending::new(move || {
+        let attempts = Arc::clone(&attempts_clone);
+        Box::pin(async move {
+            if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                panic!("transient failure");
+            }
+            "ok"
+        })
+    });
+
+    assert_eq!(flaky.retry(3).call().await, "ok");
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
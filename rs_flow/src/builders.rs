@@ -4,6 +4,8 @@
 //! fixed values, channels, and other sources.
 
 use crate::flow::Flow;
+use crate::operators::TimingOptions;
+use rs_coroutine_core::CoroutineScope;
 use std::future::Future;
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -96,16 +98,55 @@ where
     Flow::new(move |collector| {
         let builder = Arc::clone(&builder);
         async move {
-            let (tx, mut rx) = mpsc::channel(16);
+            let (tx, rx) = mpsc::channel(16);
 
             let fut = builder(tx);
             let producer = tokio::spawn(fut);
 
+            collector.emit_all(rx).await;
+
+            let _ = producer.await;
+        }
+    })
+}
+
+/// Create a flow whose producer is launched as a coroutine in `scope`.
+/// This is like Kotlin's `produce` - unlike `channel_flow`, the producer's
+/// lifetime is tied to the scope, so cancelling the scope stops production
+/// and completes the flow.
+///
+/// # Example
+/// ```ignore
+/// let flow = produce_flow(&scope, |tx| async move {
+///     loop {
+///         tx.send(poll().await).await.ok();
+///     }
+/// });
+/// ```
+pub fn produce_flow<T, F, Fut>(scope: &CoroutineScope, builder: F) -> Flow<T>
+where
+    T: Send + 'static,
+    F: Fn(mpsc::Sender<T>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let scope = scope.clone();
+    let builder = Arc::new(builder);
+    Flow::new(move |collector| {
+        let (tx, mut rx) = mpsc::channel(16);
+
+        let fut = builder(tx);
+        let cancel_token = scope.cancel_token.clone();
+        scope.launch(async move {
+            tokio::select! {
+                _ = fut => {}
+                _ = cancel_token.cancelled() => {}
+            }
+        });
+
+        async move {
             while let Some(value) = rx.recv().await {
                 collector.emit(value).await;
             }
-
-            let _ = producer.await;
         }
     })
 }
@@ -173,6 +214,50 @@ pub fn interval_flow(period: std::time::Duration) -> Flow<u64> {
     })
 }
 
+/// Create an interval flow that ticks on the dispatcher in `options`, via
+/// `flow_on`. Ticks are still emitted on the collecting context.
+///
+/// # Example
+/// ```ignore
+/// let ticks = interval_flow_on(Duration::from_secs(1), timer_dispatcher.clone());
+/// ```
+pub fn interval_flow_on(
+    period: std::time::Duration,
+    options: impl Into<TimingOptions>,
+) -> Flow<u64> {
+    options.into().pin(interval_flow(period))
+}
+
+/// Create a flow that emits each line read from `reader`, without the
+/// line terminator. Reading stops at end of input or on the first IO error.
+///
+/// The flow is single-shot: the reader is consumed by the first collection,
+/// so later collections emit nothing.
+///
+/// # Example
+/// ```ignore
+/// let file = tokio::io::BufReader::new(File::open("app.log").await?);
+/// let errors = lines_flow(file).filter_sync(|line| line.contains("ERROR"));
+/// ```
+pub fn lines_flow<R>(reader: R) -> Flow<String>
+where
+    R: tokio::io::AsyncBufRead + Unpin + Send + 'static,
+{
+    let reader = Arc::new(std::sync::Mutex::new(Some(reader)));
+    Flow::new(move |collector| {
+        let reader = reader.lock().unwrap_or_else(|e| e.into_inner()).take();
+        async move {
+            let Some(reader) = reader else {
+                return;
+            };
+            let mut lines = tokio::io::AsyncBufReadExt::lines(reader);
+            while let Ok(Some(line)) = lines.next_line().await {
+                collector.emit(line).await;
+            }
+        }
+    })
+}
+
 /// Macro to create a flow from a list of values (like Kotlin's flowOf)
 ///
 /// # Example
@@ -262,4 +347,55 @@ mod tests {
         let result = flow.to_vec().await;
         assert_eq!(result, vec![42, 42, 42]);
     }
+
+    #[tokio::test]
+    async fn test_produce_flow() {
+        let scope = CoroutineScope::new(rs_coroutine_core::Dispatchers::main());
+        let flow = produce_flow(&scope, |tx| async move {
+            for i in 1..=3 {
+                tx.send(i).await.ok();
+            }
+        });
+
+        let result = flow.to_vec().await;
+        assert_eq!(result, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_produce_flow_stops_on_scope_cancel() {
+        let scope = CoroutineScope::new(rs_coroutine_core::Dispatchers::main());
+        let flow = produce_flow(&scope, |tx| async move {
+            let mut i = 0;
+            loop {
+                if tx.send(i).await.is_err() {
+                    break;
+                }
+                i += 1;
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            }
+        });
+
+        let canceller = scope.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            canceller.cancel();
+        });
+
+        let result = tokio::time::timeout(std::time::Duration::from_secs(2), flow.to_vec())
+            .await
+            .expect("collection should end when the scope is cancelled");
+        assert!(!result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lines_flow() {
+        let reader = std::io::Cursor::new(b"first\nsecond\r\nthird".to_vec());
+        let flow = lines_flow(reader);
+
+        let result = flow.clone().to_vec().await;
+        assert_eq!(result, vec!["first", "second", "third"]);
+
+        // The reader was consumed by the first collection
+        assert!(flow.to_vec().await.is_empty());
+    }
 }
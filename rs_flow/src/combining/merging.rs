@@ -0,0 +1,200 @@
+//! Combining a list of flows into one
+//!
+//! These are re-exported from `combining`.
+
+use crate::flow::Flow;
+use crate::task_guard::AbortOnDrop;
+use tokio::sync::mpsc;
+
+/// Merge multiple flows into a single flow.
+/// Values are emitted as they arrive from any source flow.
+/// An empty `flows` gives a flow that completes without emitting.
+///
+/// A source that panics stops contributing values while the others run on;
+/// use `merge_strict` to re-raise the panic instead.
+///
+/// # Example
+/// ```ignore
+/// let merged = merge(vec![flow1, flow2, flow3]);
+/// ```
+pub fn merge<T>(flows: Vec<Flow<T>>) -> Flow<T>
+where
+    T: Send + 'static,
+{
+    merge_sources(flows, false)
+}
+
+/// Merge multiple flows like `merge`, but re-raise the first panic of any
+/// source in the collecting task, stopping the remaining sources.
+///
+/// # Example
+/// ```ignore
+/// let merged = merge_strict(vec![primary, replica]);
+/// ```
+pub fn merge_strict<T>(flows: Vec<Flow<T>>) -> Flow<T>
+where
+    T: Send + 'static,
+{
+    merge_sources(flows, true)
+}
+
+fn merge_sources<T>(flows: Vec<Flow<T>>, strict: bool) -> Flow<T>
+where
+    T: Send + 'static,
+{
+    Flow::new(move |collector| {
+        let flows = flows.clone();
+
+        async move {
+            let (tx, mut rx) = mpsc::channel::<T>(16);
+            let (panic_tx, mut panics) = mpsc::unbounded_channel();
+
+            // Spawn a task for each flow
+            let (tasks, _guards): (Vec<_>, Vec<_>) = flows
+                .into_iter()
+                .map(|flow| {
+                    let tx = tx.clone();
+                    let panic_tx = panic_tx.clone();
+                    let (task, guard) = AbortOnDrop::wrap(async move {
+                        // Keeps the value channel open until a panic is reported
+                        let open = tx.clone();
+                        let collected = flow.collect(move |value| {
+                            let tx = tx.clone();
+                            async move {
+                                let _ = tx.send(value).await;
+                            }
+                        });
+                        let collected = std::panic::AssertUnwindSafe(collected);
+                        if let Err(panic) = futures::FutureExt::catch_unwind(collected).await {
+                            let _ = panic_tx.send(panic);
+                        }
+                        drop(open);
+                    });
+                    (tokio::spawn(task), guard)
+                })
+                .unzip();
+
+            // Drop our senders so rx will close when all tasks complete
+            drop(tx);
+            drop(panic_tx);
+
+            // Emit merged values. Each task holds a value sender until it has
+            // reported its panic, so checking panics first never misses one.
+            loop {
+                tokio::select! {
+                    biased;
+                    Some(panic) = panics.recv(), if strict => std::panic::resume_unwind(panic),
+                    value = rx.recv() => match value {
+                        Some(value) => collector.emit(value).await,
+                        None => break,
+                    },
+                }
+            }
+
+            // Wait for all tasks
+            for task in tasks {
+                let _ = task.await;
+            }
+        }
+    })
+}
+
+/// Interleave multiple flows round-robin.
+/// Takes one value from each flow in turn, skipping flows that have
+/// completed, until all of them are exhausted.
+///
+/// # Example
+/// ```ignore
+/// let fair = interleave(vec![high_priority, low_priority]);
+/// ```
+pub fn interleave<T>(flows: Vec<Flow<T>>) -> Flow<T>
+where
+    T: Send + 'static,
+{
+    Flow::new(move |collector| {
+        let flows = flows.clone();
+
+        async move {
+            // Each source runs ahead by at most one value while waiting its turn
+            let (mut receivers, _guards) = spawn_sources(flows, 1);
+
+            while !receivers.is_empty() {
+                let mut index = 0;
+                while index < receivers.len() {
+                    match receivers[index].recv().await {
+                        Some(value) => {
+                            collector.emit(value).await;
+                            index += 1;
+                        }
+                        None => {
+                            receivers.remove(index);
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Merge flows that are each sorted in ascending order into one sorted flow.
+/// Holds one pending value per source and always emits the smallest, taking
+/// the earlier source on ties.
+///
+/// # Example
+/// ```ignore
+/// let timeline = merge_sorted(vec![local_events, remote_events]);
+/// ```
+pub fn merge_sorted<T>(flows: Vec<Flow<T>>) -> Flow<T>
+where
+    T: Ord + Send + 'static,
+{
+    Flow::new(move |collector| {
+        let flows = flows.clone();
+
+        async move {
+            let (mut receivers, _guards) = spawn_sources(flows, 1);
+
+            // The head of each source; `None` once the source has completed
+            let mut heads = Vec::with_capacity(receivers.len());
+            for rx in &mut receivers {
+                heads.push(rx.recv().await);
+            }
+
+            loop {
+                let smallest = heads
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, head)| head.as_ref().map(|value| (index, value)))
+                    .min_by(|a, b| a.1.cmp(b.1))
+                    .map(|(index, _)| index);
+                let Some(index) = smallest else {
+                    break;
+                };
+                if let Some(value) = heads[index].take() {
+                    collector.emit(value).await;
+                }
+                heads[index] = receivers[index].recv().await;
+            }
+        }
+    })
+}
+
+/// Collect each flow in its own task, feeding a channel of `capacity` per
+/// source. The tasks stop when the returned guards are dropped.
+fn spawn_sources<T>(
+    flows: Vec<Flow<T>>,
+    capacity: usize,
+) -> (Vec<mpsc::Receiver<T>>, Vec<AbortOnDrop>)
+where
+    T: Send + 'static,
+{
+    flows
+        .into_iter()
+        .map(|flow| {
+            let (tx, rx) = mpsc::channel::<T>(capacity);
+            let (task, guard) = AbortOnDrop::wrap(async move { flow.send_to(tx).await });
+            tokio::spawn(task);
+            (rx, guard)
+        })
+        .unzip()
+}
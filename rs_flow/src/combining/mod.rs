@@ -0,0 +1,844 @@
+//! Combining operators for Flow
+//!
+//! These operators allow you to combine multiple flows into one.
+
+use crate::flow::Flow;
+use crate::task_guard::AbortOnDrop;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+/// Combining operators for Flow
+pub trait FlowCombining<T>: Sized
+where
+    T: Send + 'static,
+{
+    /// Combine this flow with another, emitting a new value whenever either flow emits.
+    /// The transform function receives the latest values from both flows.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let combined = flow1.combine(flow2, |a, b| a + b);
+    /// ```
+    fn combine<U, R, F>(self, other: Flow<U>, transform: F) -> Flow<R>
+    where
+        U: Send + Clone + 'static,
+        R: Send + 'static,
+        T: Clone,
+        F: Fn(T, U) -> R + Send + Sync + 'static;
+
+    /// Like `combine`, but completes as soon as either flow completes instead
+    /// of waiting for both. Emits only once both flows have produced a value.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let quotes = prices.combine_until_either(session, |price, s| s.quote(price));
+    /// ```
+    fn combine_until_either<U, R, F>(self, other: Flow<U>, transform: F) -> Flow<R>
+    where
+        U: Send + Clone + 'static,
+        R: Send + 'static,
+        T: Clone,
+        F: Fn(T, U) -> R + Send + Sync + 'static;
+
+    /// Zip this flow with another, pairing values one-to-one.
+    /// Completes when either flow completes.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let zipped = flow1.zip(flow2, |a, b| (a, b));
+    /// ```
+    fn zip<U, R, F>(self, other: Flow<U>, transform: F) -> Flow<R>
+    where
+        U: Send + 'static,
+        R: Send + 'static,
+        F: Fn(T, U) -> R + Send + Sync + 'static;
+
+    /// Combine this flow with another, emitting only the latest value from this flow
+    /// when the other flow emits.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let sampled = data_flow.sample(tick_flow);
+    /// ```
+    fn sample<U>(self, sampler: Flow<U>) -> Flow<T>
+    where
+        U: Send + 'static,
+        T: Clone;
+
+    /// Concatenate this flow with another, emitting all values from this flow
+    /// first, then all values from the other flow.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let concatenated = flow1.concat(flow2);
+    /// ```
+    fn concat(self, other: Flow<T>) -> Flow<T>;
+
+    /// Start with the given values, then emit values from this flow.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let flow = numbers.start_with(vec![0]);
+    /// ```
+    fn start_with<I>(self, values: I) -> Flow<T>
+    where
+        I: IntoIterator<Item = T> + Clone + Send + Sync + 'static,
+        I::IntoIter: Send;
+
+    /// Accumulate values and emit them as a batch (possibly empty) each time
+    /// the signal flow emits. Remaining values are flushed when this flow completes.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let batches = events.buffer_until(flush_ticks);
+    /// ```
+    fn buffer_until<U>(self, signal: Flow<U>) -> Flow<Vec<T>>
+    where
+        U: Send + 'static;
+
+    /// Combine each value of this flow with the latest value of another flow.
+    /// Emits only when this flow emits; values arriving before the other flow
+    /// has produced anything are dropped.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let priced = orders.with_latest_from(rates, |order, rate| order.total * rate);
+    /// ```
+    fn with_latest_from<U, R, F>(self, other: Flow<U>, f: F) -> Flow<R>
+    where
+        U: Send + Clone + 'static,
+        R: Send + 'static,
+        F: Fn(T, U) -> R + Send + Sync + 'static;
+
+    /// Only emit distinct consecutive values, forgetting the last emitted
+    /// value whenever `reset` emits so the next value always passes.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let items = visible_items.distinct_until_changed_reset(page_changes);
+    /// ```
+    fn distinct_until_changed_reset<U>(self, reset: Flow<U>) -> Flow<T>
+    where
+        T: Clone + PartialEq,
+        U: Send + 'static;
+}
+
+impl<T> FlowCombining<T> for Flow<T>
+where
+    T: Send + 'static,
+{
+    fn combine<U, R, F>(self, other: Flow<U>, transform: F) -> Flow<R>
+    where
+        U: Send + Clone + 'static,
+        R: Send + 'static,
+        T: Clone,
+        F: Fn(T, U) -> R + Send + Sync + 'static,
+    {
+        combine_sources(self, other, transform, false)
+    }
+
+    fn combine_until_either<U, R, F>(self, other: Flow<U>, transform: F) -> Flow<R>
+    where
+        U: Send + Clone + 'static,
+        R: Send + 'static,
+        T: Clone,
+        F: Fn(T, U) -> R + Send + Sync + 'static,
+    {
+        combine_sources(self, other, transform, true)
+    }
+
+    fn zip<U, R, F>(self, other: Flow<U>, transform: F) -> Flow<R>
+    where
+        U: Send + 'static,
+        R: Send + 'static,
+        F: Fn(T, U) -> R + Send + Sync + 'static,
+    {
+        let transform = Arc::new(transform);
+
+        Flow::new(move |collector| {
+            let upstream1 = self.clone();
+            let upstream2 = other.clone();
+            let transform = Arc::clone(&transform);
+
+            async move {
+                let (tx1, mut rx1) = mpsc::channel::<T>(16);
+                let (tx2, mut rx2) = mpsc::channel::<U>(16);
+
+                // Spawn task to collect from first flow
+                let (task1, _guard1) = AbortOnDrop::wrap(async move {
+                    upstream1
+                        .collect(move |value| {
+                            let tx = tx1.clone();
+                            async move {
+                                let _ = tx.send(value).await;
+                            }
+                        })
+                        .await;
+                });
+                let task1 = tokio::spawn(task1);
+
+                // Spawn task to collect from second flow
+                let (task2, _guard2) = AbortOnDrop::wrap(async move {
+                    upstream2
+                        .collect(move |value| {
+                            let tx = tx2.clone();
+                            async move {
+                                let _ = tx.send(value).await;
+                            }
+                        })
+                        .await;
+                });
+                let task2 = tokio::spawn(task2);
+
+                // Zip values
+                while let (Some(v1), Some(v2)) = (rx1.recv().await, rx2.recv().await) {
+                    collector.emit(transform(v1, v2)).await;
+                }
+
+                let _ = task1.await;
+                let _ = task2.await;
+            }
+        })
+    }
+
+    fn sample<U>(self, sampler: Flow<U>) -> Flow<T>
+    where
+        U: Send + 'static,
+        T: Clone,
+    {
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let sampler = sampler.clone();
+
+            async move {
+                let latest: Arc<Mutex<Option<T>>> = Arc::new(Mutex::new(None));
+                let (tx, mut rx) = mpsc::channel::<()>(16);
+
+                // Spawn task to collect from data flow
+                let latest_clone = Arc::clone(&latest);
+                let (task1, _guard1) = AbortOnDrop::wrap(async move {
+                    upstream
+                        .collect(move |value| {
+                            let latest = Arc::clone(&latest_clone);
+                            async move {
+                                *latest.lock().await = Some(value);
+                            }
+                        })
+                        .await;
+                });
+                let task1 = tokio::spawn(task1);
+
+                // Spawn task to collect from sampler flow
+                let (task2, _guard2) = AbortOnDrop::wrap(async move {
+                    sampler
+                        .collect(move |_| {
+                            let tx = tx.clone();
+                            async move {
+                                let _ = tx.send(()).await;
+                            }
+                        })
+                        .await;
+                });
+                let task2 = tokio::spawn(task2);
+
+                // Emit sampled values
+                while let Some(()) = rx.recv().await {
+                    if let Some(value) = latest.lock().await.clone() {
+                        collector.emit(value).await;
+                    }
+                }
+
+                let _ = task1.await;
+                let _ = task2.await;
+            }
+        })
+    }
+
+    fn with_latest_from<U, R, F>(self, other: Flow<U>, f: F) -> Flow<R>
+    where
+        U: Send + Clone + 'static,
+        R: Send + 'static,
+        F: Fn(T, U) -> R + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let other = other.clone();
+            let f = Arc::clone(&f);
+
+            async move {
+                let latest: Arc<Mutex<Option<U>>> = Arc::new(Mutex::new(None));
+
+                // Spawn task to track the latest value of the other flow
+                let latest_clone = Arc::clone(&latest);
+                let (task, _guard) = AbortOnDrop::wrap(async move {
+                    other
+                        .collect(move |value| {
+                            let latest = Arc::clone(&latest_clone);
+                            async move {
+                                *latest.lock().await = Some(value);
+                            }
+                        })
+                        .await;
+                });
+                tokio::spawn(task);
+
+                upstream
+                    .collect(move |value| {
+                        let collector = collector.clone();
+                        let latest = Arc::clone(&latest);
+                        let f = Arc::clone(&f);
+                        async move {
+                            let current = latest.lock().await.clone();
+                            if let Some(other_value) = current {
+                                collector.emit(f(value, other_value)).await;
+                            }
+                        }
+                    })
+                    .await;
+            }
+        })
+    }
+
+    fn distinct_until_changed_reset<U>(self, reset: Flow<U>) -> Flow<T>
+    where
+        T: Clone + PartialEq,
+        U: Send + 'static,
+    {
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let reset = reset.clone();
+
+            async move {
+                let last: Arc<Mutex<Option<T>>> = Arc::new(Mutex::new(None));
+
+                // Spawn task to forget the last value on each reset signal
+                let last_clone = Arc::clone(&last);
+                let (task, _guard) = AbortOnDrop::wrap(async move {
+                    reset
+                        .collect(move |_| {
+                            let last = Arc::clone(&last_clone);
+                            async move {
+                                *last.lock().await = None;
+                            }
+                        })
+                        .await;
+                });
+                tokio::spawn(task);
+
+                upstream
+                    .collect(move |value| {
+                        let collector = collector.clone();
+                        let last = Arc::clone(&last);
+                        async move {
+                            let mut guard = last.lock().await;
+                            if guard.as_ref() == Some(&value) {
+                                return;
+                            }
+                            *guard = Some(value.clone());
+                            drop(guard);
+                            collector.emit(value).await;
+                        }
+                    })
+                    .await;
+            }
+        })
+    }
+
+    fn concat(self, other: Flow<T>) -> Flow<T> {
+        Flow::new(move |collector| {
+            let first = self.clone();
+            let second = other.clone();
+
+            async move {
+                // Collect from first flow
+                let collector_clone = collector.clone();
+                first
+                    .collect(move |value| {
+                        let collector = collector_clone.clone();
+                        async move {
+                            collector.emit(value).await;
+                        }
+                    })
+                    .await;
+
+                // Then collect from second flow
+                second
+                    .collect(move |value| {
+                        let collector = collector.clone();
+                        async move {
+                            collector.emit(value).await;
+                        }
+                    })
+                    .await;
+            }
+        })
+    }
+
+    fn start_with<I>(self, values: I) -> Flow<T>
+    where
+        I: IntoIterator<Item = T> + Clone + Send + Sync + 'static,
+        I::IntoIter: Send,
+    {
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let values = values.clone();
+
+            async move {
+                // Emit initial values
+                for value in values {
+                    collector.emit(value).await;
+                }
+
+                // Then collect from upstream
+                upstream
+                    .collect(move |value| {
+                        let collector = collector.clone();
+                        async move {
+                            collector.emit(value).await;
+                        }
+                    })
+                    .await;
+            }
+        })
+    }
+
+    fn buffer_until<U>(self, signal: Flow<U>) -> Flow<Vec<T>>
+    where
+        U: Send + 'static,
+    {
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let signal = signal.clone();
+
+            async move {
+                let (data_tx, mut data_rx) = mpsc::channel::<T>(16);
+                let (signal_tx, mut signal_rx) = mpsc::channel::<()>(16);
+
+                // Spawn task to collect from data flow
+                let (task1, _guard1) = AbortOnDrop::wrap(async move {
+                    upstream
+                        .collect(move |value| {
+                            let tx = data_tx.clone();
+                            async move {
+                                let _ = tx.send(value).await;
+                            }
+                        })
+                        .await;
+                });
+                let task1 = tokio::spawn(task1);
+
+                // Spawn task to collect from signal flow
+                let (task2, signal_guard) = AbortOnDrop::wrap(async move {
+                    signal
+                        .collect(move |_| {
+                            let tx = signal_tx.clone();
+                            async move {
+                                let _ = tx.send(()).await;
+                            }
+                        })
+                        .await;
+                });
+                tokio::spawn(task2);
+
+                let mut batch = Vec::new();
+                let mut signal_open = true;
+
+                loop {
+                    tokio::select! {
+                        value = data_rx.recv() => match value {
+                            Some(value) => batch.push(value),
+                            None => break, // Data flow completed
+                        },
+                        tick = signal_rx.recv(), if signal_open => match tick {
+                            Some(()) => collector.emit(std::mem::take(&mut batch)).await,
+                            None => signal_open = false,
+                        },
+                    }
+                }
+
+                if !batch.is_empty() {
+                    collector.emit(batch).await;
+                }
+
+                // The signal may be infinite; it is only needed while data flows
+                drop(signal_guard);
+                let _ = task1.await;
+            }
+        })
+    }
+}
+
+/// Shared body of `combine` and `combine_until_either`. With `until_either`
+/// set, collection stops at the first completion of either flow.
+fn combine_sources<T, U, R, F>(
+    first: Flow<T>,
+    second: Flow<U>,
+    transform: F,
+    until_either: bool,
+) -> Flow<R>
+where
+    T: Send + Clone + 'static,
+    U: Send + Clone + 'static,
+    R: Send + 'static,
+    F: Fn(T, U) -> R + Send + Sync + 'static,
+{
+    let transform = Arc::new(transform);
+
+    Flow::new(move |collector| {
+        let upstream1 = first.clone();
+        let upstream2 = second.clone();
+        let transform = Arc::clone(&transform);
+
+        async move {
+            let latest1: Arc<Mutex<Option<T>>> = Arc::new(Mutex::new(None));
+            let latest2: Arc<Mutex<Option<U>>> = Arc::new(Mutex::new(None));
+
+            // `None` marks that one of the flows has completed
+            let (tx, mut rx) = mpsc::channel::<Option<(Option<T>, Option<U>)>>(16);
+
+            // Spawn task to collect from first flow
+            let tx1 = tx.clone();
+            let latest1_clone = Arc::clone(&latest1);
+            let latest2_clone = Arc::clone(&latest2);
+            let (task1, _guard1) = AbortOnDrop::wrap(async move {
+                let tx = tx1.clone();
+                upstream1
+                    .collect(move |value| {
+                        let tx = tx1.clone();
+                        let latest1 = Arc::clone(&latest1_clone);
+                        let latest2 = Arc::clone(&latest2_clone);
+                        async move {
+                            *latest1.lock().await = Some(value.clone());
+                            let l2 = latest2.lock().await.clone();
+                            let _ = tx.send(Some((Some(value), l2))).await;
+                        }
+                    })
+                    .await;
+                let _ = tx.send(None).await;
+            });
+            tokio::spawn(task1);
+
+            // Spawn task to collect from second flow
+            let tx2 = tx.clone();
+            let latest1_clone = Arc::clone(&latest1);
+            let latest2_clone = Arc::clone(&latest2);
+            let (task2, _guard2) = AbortOnDrop::wrap(async move {
+                let tx = tx2.clone();
+                upstream2
+                    .collect(move |value| {
+                        let tx = tx2.clone();
+                        let latest1 = Arc::clone(&latest1_clone);
+                        let latest2 = Arc::clone(&latest2_clone);
+                        async move {
+                            *latest2.lock().await = Some(value.clone());
+                            let l1 = latest1.lock().await.clone();
+                            let _ = tx.send(Some((l1, Some(value)))).await;
+                        }
+                    })
+                    .await;
+                let _ = tx.send(None).await;
+            });
+            tokio::spawn(task2);
+
+            // Drop our sender so rx will close when tasks complete
+            drop(tx);
+
+            // Emit combined values; on an early stop the guards end the
+            // remaining flow
+            while let Some(message) = rx.recv().await {
+                match message {
+                    Some((Some(a), Some(b))) => collector.emit(transform(a, b)).await,
+                    Some(_) => {}
+                    None if until_either => break,
+                    None => {}
+                }
+            }
+        }
+    })
+}
+
+mod merging;
+
+pub use merging::{interleave, merge, merge_sorted, merge_strict};
+
+/// Macro to merge multiple flows
+///
+/// # Example
+/// ```ignore
+/// let merged = merge!(flow1, flow2, flow3);
+/// ```
+#[macro_export]
+macro_rules! merge {
+    ($($flow:expr),+ $(,)?) => {
+        $crate::combining::merge(vec![$($flow),+])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::flow_of;
+    use crate::flow::flow;
+    use crate::terminal::FlowTerminal;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_zip() {
+        let flow1 = flow(|c| async move {
+            c.emit(1).await;
+            c.emit(2).await;
+            c.emit(3).await;
+        });
+
+        let flow2 = flow(|c| async move {
+            c.emit("a").await;
+            c.emit("b").await;
+            c.emit("c").await;
+        });
+
+        let zipped = flow1.zip(flow2, |a, b| format!("{}{}", a, b));
+        let result = zipped.to_vec().await;
+
+        assert_eq!(result, vec!["1a", "2b", "3c"]);
+    }
+
+    #[tokio::test]
+    async fn test_concat() {
+        let flow1 = flow(|c| async move {
+            c.emit(1).await;
+            c.emit(2).await;
+        });
+
+        let flow2 = flow(|c| async move {
+            c.emit(3).await;
+            c.emit(4).await;
+        });
+
+        let concatenated = flow1.concat(flow2);
+        let result = concatenated.to_vec().await;
+
+        assert_eq!(result, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_start_with() {
+        let flow = flow(|c| async move {
+            c.emit(3).await;
+            c.emit(4).await;
+        })
+        .start_with(vec![1, 2]);
+
+        let result = flow.to_vec().await;
+        assert_eq!(result, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_merge() {
+        let flow1 = flow(|c| async move {
+            c.emit(1).await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            c.emit(3).await;
+        });
+
+        let flow2 = flow(|c| async move {
+            tokio::time::sleep(Duration::from_millis(25)).await;
+            c.emit(2).await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            c.emit(4).await;
+        });
+
+        let merged = merge(vec![flow1, flow2]);
+        let result = merged.to_vec().await;
+
+        // Values arrive in time order (roughly)
+        assert_eq!(result.len(), 4);
+        assert!(result.contains(&1));
+        assert!(result.contains(&2));
+        assert!(result.contains(&3));
+        assert!(result.contains(&4));
+    }
+
+    #[tokio::test]
+    async fn test_merge_empty_completes_without_values() {
+        assert!(merge(Vec::<Flow<i32>>::new()).to_vec().await.is_empty());
+        assert!(merge_strict(Vec::<Flow<i32>>::new())
+            .to_vec()
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_merge_strict_reraises_source_panic() {
+        let healthy = flow(|c| async move {
+            for i in 0..3 {
+                c.emit(i).await;
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        });
+        let failing = flow(|c| async move {
+            c.emit(100).await;
+            panic!("source failed");
+        });
+
+        let lenient = merge(vec![healthy.clone(), failing.clone()]).to_vec().await;
+        assert_eq!(lenient.len(), 4);
+
+        let strict = merge_strict(vec![healthy, failing]).to_vec();
+        let panic = tokio::spawn(strict).await.unwrap_err().into_panic();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"source failed"));
+    }
+
+    #[tokio::test]
+    async fn test_buffer_until() {
+        let data = flow(|c| async move {
+            c.emit(1).await;
+            c.emit(2).await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            c.emit(3).await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            c.emit(4).await;
+        });
+
+        let ticks = flow(|c| async move {
+            tokio::time::sleep(Duration::from_millis(25)).await;
+            c.emit(()).await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            c.emit(()).await;
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            c.emit(()).await;
+        });
+
+        let result = data.buffer_until(ticks).to_vec().await;
+        assert_eq!(result, vec![vec![1, 2], vec![3], vec![4]]);
+    }
+
+    #[tokio::test]
+    async fn test_buffer_until_stops_sources_when_dropped() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let polls = Arc::new(AtomicUsize::new(0));
+        let endless = |polls: Arc<AtomicUsize>| {
+            flow(move |c| {
+                let polls = Arc::clone(&polls);
+                async move {
+                    loop {
+                        polls.fetch_add(1, Ordering::SeqCst);
+                        c.emit(()).await;
+                        tokio::time::sleep(Duration::from_millis(2)).await;
+                    }
+                }
+            })
+        };
+
+        let batches = endless(Arc::clone(&polls)).buffer_until(endless(Arc::clone(&polls)));
+        let collected = batches.collect(|_| async {});
+        let _ = tokio::time::timeout(Duration::from_millis(20), collected).await;
+
+        tokio::task::yield_now().await;
+        let after_drop = polls.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(polls.load(Ordering::SeqCst), after_drop);
+    }
+
+    #[tokio::test]
+    async fn test_interleave() {
+        let first = flow(|c| async move {
+            for value in [1, 2, 3] {
+                c.emit(value).await;
+            }
+        });
+        let second = flow(|c| async move {
+            for value in [10, 20] {
+                c.emit(value).await;
+            }
+        });
+
+        let result = interleave(vec![first, second]).to_vec().await;
+        assert_eq!(result, vec![1, 10, 2, 20, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_distinct_until_changed_reset() {
+        let values = flow(|c| async move {
+            c.emit(1).await;
+            c.emit(1).await; // Suppressed
+            tokio::time::sleep(Duration::from_millis(40)).await;
+            c.emit(1).await; // Passes after the reset
+            c.emit(1).await; // Suppressed
+            c.emit(2).await;
+        });
+        let reset = flow(|c| async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            c.emit(()).await;
+        });
+
+        let result = values.distinct_until_changed_reset(reset).to_vec().await;
+        assert_eq!(result, vec![1, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_sorted() {
+        let result = merge_sorted(vec![
+            flow_of(vec![1, 4, 7]),
+            flow_of(vec![2, 5]),
+            flow_of(vec![3, 6]),
+        ])
+        .to_vec()
+        .await;
+        assert_eq!(result, vec![1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[tokio::test]
+    async fn test_with_latest_from() {
+        let primary = flow(|c| async move {
+            c.emit(1).await; // Dropped: no secondary value yet
+            tokio::time::sleep(Duration::from_millis(40)).await;
+            c.emit(2).await;
+            tokio::time::sleep(Duration::from_millis(60)).await;
+            c.emit(3).await;
+        });
+        let secondary = flow(|c| async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            c.emit('a').await;
+            tokio::time::sleep(Duration::from_millis(40)).await;
+            c.emit('b').await;
+            c.emit('c').await;
+        });
+
+        let result = primary
+            .with_latest_from(secondary, |n, letter| (n, letter))
+            .to_vec()
+            .await;
+        assert_eq!(result, vec![(2, 'a'), (3, 'c')]);
+    }
+
+    #[tokio::test]
+    async fn test_combine_until_either_stops_with_shorter_flow() {
+        let short = flow(|c| async move {
+            c.emit(1).await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            c.emit(2).await;
+            tokio::time::sleep(Duration::from_millis(30)).await;
+        });
+        let long = flow(|c| async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            c.emit('x').await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            c.emit('y').await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            c.emit('z').await; // After the short flow completed
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        });
+
+        let started = std::time::Instant::now();
+        let result = short
+            .combine_until_either(long, |n, letter| (n, letter))
+            .to_vec()
+            .await;
+
+        assert_eq!(result, vec![(1, 'x'), (2, 'x'), (2, 'y')]);
+        assert!(started.elapsed() < Duration::from_millis(300));
+    }
+}
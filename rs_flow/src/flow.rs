@@ -1,12 +1,38 @@
 use std::future::Future;
 use std::pin::Pin;
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use tokio::sync::mpsc;
 
 type FlowFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type BatchFn<T> = Arc<dyn Fn(Vec<T>) -> FlowFuture + Send + Sync>;
 
 /// A collector that receives emitted values
+///
+/// Emissions into a collector must not overlap: a flow emits its values one
+/// after another. Debug builds panic when an emission starts while another
+/// is still in progress, e.g. when several tasks emit into the same
+/// collector; use `channel_flow` to produce values from multiple tasks.
 pub struct FlowCollector<T> {
     emit_fn: Arc<dyn Fn(T) -> FlowFuture + Send + Sync>,
+    /// Receives whole batches from `emit_batch` when the collector can
+    /// take them at once, as channel-backed collectors do
+    batch_fn: Option<BatchFn<T>>,
+    /// Set while an emission is in progress
+    #[cfg(debug_assertions)]
+    emitting: Arc<AtomicBool>,
+}
+
+/// Clears the collector's in-progress flag when an emission finishes or is dropped
+#[cfg(debug_assertions)]
+struct Emission(Arc<AtomicBool>);
+
+#[cfg(debug_assertions)]
+impl Drop for Emission {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
 }
 
 impl<T> FlowCollector<T> {
@@ -18,19 +44,111 @@ impl<T> FlowCollector<T> {
     {
         Self {
             emit_fn: Arc::new(move |value| Box::pin(emit_fn(value))),
+            batch_fn: None,
+            #[cfg(debug_assertions)]
+            emitting: Arc::default(),
         }
     }
 
+    /// Hand batches from `emit_batch` to `batch_fn` in one call
+    pub(crate) fn with_batch<F, Fut>(mut self, batch_fn: F) -> Self
+    where
+        F: Fn(Vec<T>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.batch_fn = Some(Arc::new(move |values| Box::pin(batch_fn(values))));
+        self
+    }
+
     /// Emit a value to the collector
     pub async fn emit(&self, value: T) {
+        #[cfg(debug_assertions)]
+        let _emission = self.begin_emission();
         (self.emit_fn)(value).await
     }
+
+    /// Emit a batch of values in order
+    ///
+    /// Channel-backed collectors, such as the upstream side of `buffer` and
+    /// `flow_on`, take the batch with one await per channel-capacity chunk;
+    /// other collectors receive the values one by one.
+    ///
+    /// # Example
+    /// ```ignore
+    /// collector.emit_batch(rows).await;
+    /// ```
+    pub async fn emit_batch(&self, values: Vec<T>) {
+        #[cfg(debug_assertions)]
+        let _emission = self.begin_emission();
+        match &self.batch_fn {
+            Some(batch_fn) => batch_fn(values).await,
+            None => {
+                for value in values {
+                    (self.emit_fn)(value).await;
+                }
+            }
+        }
+    }
+
+    /// Emit everything received from `rx` until it closes, passing on the
+    /// values that are already waiting as one batch
+    pub(crate) async fn emit_all(&self, mut rx: mpsc::Receiver<T>) {
+        let limit = rx.max_capacity();
+        let mut batch = Vec::with_capacity(limit);
+        while rx.recv_many(&mut batch, limit).await > 0 {
+            self.emit_batch(std::mem::replace(&mut batch, Vec::with_capacity(limit)))
+                .await;
+        }
+    }
+
+    /// Mark an emission as in progress, panicking if one already is
+    #[cfg(debug_assertions)]
+    fn begin_emission(&self) -> Emission {
+        if self.emitting.swap(true, Ordering::SeqCst) {
+            panic!(
+                "Flow invariant violated: concurrent emission into the same FlowCollector. \
+                 Emit sequentially, or use channel_flow to emit from multiple tasks"
+            );
+        }
+        Emission(Arc::clone(&self.emitting))
+    }
+}
+
+/// Helpers for emitting into a collector of `Result` values
+///
+/// # Example
+/// ```ignore
+/// let results = flow(|collector| async move {
+///     collector.emit_ok(1).await;
+///     collector.emit_err("invalid").await;
+/// });
+/// ```
+#[allow(async_fn_in_trait)]
+pub trait ResultCollectorExt<T, E> {
+    /// Emit `Ok(value)`
+    async fn emit_ok(&self, value: T);
+
+    /// Emit `Err(err)`
+    async fn emit_err(&self, err: E);
+}
+
+impl<T, E> ResultCollectorExt<T, E> for FlowCollector<Result<T, E>> {
+    async fn emit_ok(&self, value: T) {
+        self.emit(Ok(value)).await
+    }
+
+    async fn emit_err(&self, err: E) {
+        self.emit(Err(err)).await
+    }
 }
 
 impl<T> Clone for FlowCollector<T> {
     fn clone(&self) -> Self {
         Self {
             emit_fn: Arc::clone(&self.emit_fn),
+            batch_fn: self.batch_fn.clone(),
+            #[cfg(debug_assertions)]
+            emitting: Arc::clone(&self.emitting),
         }
     }
 }
@@ -73,6 +191,34 @@ where
         let collector = FlowCollector::new(on_value);
         (self.collect_fn)(collector).await
     }
+
+    /// Collect values from this flow, sending each one into `tx`. Batches
+    /// reserve channel capacity once per chunk instead of once per value.
+    pub(crate) async fn send_to(&self, tx: mpsc::Sender<T>) {
+        let batch_tx = tx.clone();
+        let collector = FlowCollector::new(move |value| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(value).await;
+            }
+        })
+        .with_batch(move |values: Vec<T>| {
+            let tx = batch_tx.clone();
+            async move {
+                let mut values = values.into_iter().peekable();
+                while values.peek().is_some() {
+                    let chunk: Vec<T> = values.by_ref().take(tx.max_capacity()).collect();
+                    let Ok(permits) = tx.reserve_many(chunk.len()).await else {
+                        return; // Receiver dropped
+                    };
+                    for (permit, value) in permits.zip(chunk) {
+                        permit.send(value);
+                    }
+                }
+            }
+        });
+        (self.collect_fn)(collector).await
+    }
 }
 
 impl<T> Clone for Flow<T> {
@@ -102,3 +248,101 @@ macro_rules! flow_block {
         })
     }};
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::FlowTerminal;
+
+    #[tokio::test]
+    async fn test_emit_ok_and_err() {
+        let results = flow(|collector: FlowCollector<Result<i32, String>>| async move {
+            collector.emit_ok(1).await;
+            collector.emit_err("bad input".to_string()).await;
+            collector.emit_ok(3).await;
+        });
+
+        let values = results.to_vec().await;
+        assert_eq!(values, vec![Ok(1), Err("bad input".to_string()), Ok(3)]);
+    }
+
+    #[tokio::test]
+    async fn test_emit_batch() {
+        let numbers = flow(|collector| async move {
+            collector.emit_batch(vec![1, 2, 3]).await;
+            collector.emit(4).await;
+            collector.emit_batch((5..=1000).collect()).await;
+        });
+
+        let values = numbers.to_vec().await;
+        assert_eq!(values, (1..=1000).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_emit_batch_crosses_channels_in_chunks() {
+        use crate::operators::FlowExt;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let numbers = flow(|collector| async move {
+            collector.emit_batch((1..=1000).collect()).await;
+        })
+        .buffer(16);
+
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let batches = Arc::new(AtomicUsize::new(0));
+        let collector = FlowCollector::new({
+            let received = Arc::clone(&received);
+            move |value| {
+                received.lock().unwrap().push(value);
+                async {}
+            }
+        })
+        .with_batch({
+            let received = Arc::clone(&received);
+            let batches = Arc::clone(&batches);
+            move |values: Vec<i32>| {
+                batches.fetch_add(1, Ordering::SeqCst);
+                received.lock().unwrap().extend(values);
+                async {}
+            }
+        });
+        (numbers.collect_fn)(collector).await;
+
+        assert_eq!(*received.lock().unwrap(), (1..=1000).collect::<Vec<_>>());
+        let batches = batches.load(Ordering::SeqCst);
+        assert!(
+            batches <= 1000 / 8,
+            "expected chunked delivery, got {batches} batches"
+        );
+    }
+
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    async fn test_concurrent_emit_panics() {
+        let collector = FlowCollector::new(|_: i32| async {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        });
+
+        let first = tokio::spawn({
+            let collector = collector.clone();
+            async move { collector.emit(1).await }
+        });
+        let second = tokio::spawn({
+            let collector = collector.clone();
+            async move { collector.emit(2).await }
+        });
+
+        let results = [first.await, second.await];
+        let panics: Vec<_> = results
+            .into_iter()
+            .filter_map(|result| result.err())
+            .map(|err| err.into_panic())
+            .collect();
+        assert_eq!(panics.len(), 1);
+        let message = panics[0].downcast_ref::<&str>().unwrap();
+        assert!(message.contains("concurrent emission"), "{message}");
+
+        // The collector stays usable once the overlapping emission is gone
+        collector.emit(3).await;
+    }
+}
@@ -1,5 +1,41 @@
 use crate::flow::Flow;
+use crate::publisher::Publisher;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+/// How far a connected upstream may run ahead of its slowest subscriber
+const PUBLISH_BUFFER: usize = 64;
+
+/// What a `SharedFlow` collector does when it falls so far behind that
+/// values it had not received yet were overwritten
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LagPolicy {
+    /// Skip the lost values and continue with the oldest value still buffered
+    Continue,
+    /// Emit `Err(Lagged)` reporting how many values were lost, then complete
+    Close,
+}
+
+/// Emitted by a `LagPolicy::Close` collector that fell behind its `SharedFlow`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lagged {
+    /// Number of values overwritten before the collector received them
+    pub missed: u64,
+}
+
+impl std::fmt::Display for Lagged {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SharedFlow collector lagged behind by {} values",
+            self.missed
+        )
+    }
+}
+
+impl std::error::Error for Lagged {}
 
 /// A hot flow that multicasts values to all collectors
 #[derive(Clone)]
@@ -7,7 +43,7 @@ pub struct SharedFlow<T>
 where
     T: Clone + Send + 'static,
 {
-    tx: broadcast::Sender<T>,
+    publisher: Publisher<T>,
 }
 
 impl<T> SharedFlow<T>
@@ -16,20 +52,47 @@ where
 {
     /// Create a new SharedFlow with the given capacity
     pub fn new(capacity: usize) -> Self {
-        let (tx, _) = broadcast::channel(capacity);
-        Self { tx }
+        Self {
+            publisher: Publisher::new(capacity),
+        }
     }
 
     /// Emit a value to all subscribers
+    ///
+    /// This is fire-and-forget: the value is dropped if there are no
+    /// subscribers, and the oldest buffered value is overwritten if the
+    /// buffer is full. Use `emit_suspending` to wait instead.
     pub fn emit(&self, value: T) {
-        let _ = self.tx.send(value);
+        self.publisher.send_now(value);
+    }
+
+    /// Emit a value, suspending until it can be delivered without loss
+    ///
+    /// Waits until at least one subscriber is active and the slowest one has
+    /// room for the value before sending it. The emitter is woken as soon as
+    /// a collector subscribes or takes a value.
+    ///
+    /// # Example
+    /// ```ignore
+    /// events.emit_suspending(Event::Saved).await;
+    /// ```
+    pub async fn emit_suspending(&self, value: T) {
+        self.publisher.send_when_subscribed(value).await;
     }
 
     /// Convert to a cold Flow
+    ///
+    /// Each collection subscribes exactly once, when `collect` starts.
+    /// Values emitted before that point (including between `as_flow()`
+    /// and `collect()`) are not delivered, matching Kotlin's SharedFlow
+    /// semantics without replay.
+    ///
+    /// A collector that lags behind simply completes; see
+    /// `as_flow_with_lag_policy` to choose otherwise.
     pub fn as_flow(&self) -> Flow<T> {
-        let rx = self.tx.subscribe();
+        let publisher = self.publisher.clone();
         Flow::new(move |collector| {
-            let mut rx = rx.resubscribe();
+            let mut rx = publisher.subscribe();
             async move {
                 while let Ok(value) = rx.recv().await {
                     collector.emit(value).await;
@@ -38,9 +101,47 @@ where
         })
     }
 
+    /// Convert to a cold Flow whose collectors handle lagging behind the
+    /// emitter according to `policy`
+    ///
+    /// Values arrive as `Ok`; only `LagPolicy::Close` emits an `Err`, as the
+    /// last item of the collection.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let prices = prices.as_flow_with_lag_policy(LagPolicy::Close);
+    /// prices.collect(|price| async move {
+    ///     match price {
+    ///         Ok(price) => show(price),
+    ///         Err(lagged) => resync(lagged.missed).await,
+    ///     }
+    /// }).await;
+    /// ```
+    pub fn as_flow_with_lag_policy(&self, policy: LagPolicy) -> Flow<Result<T, Lagged>> {
+        let publisher = self.publisher.clone();
+        Flow::new(move |collector| {
+            let mut rx = publisher.subscribe();
+            async move {
+                loop {
+                    match rx.recv().await {
+                        Ok(value) => collector.emit(Ok(value)).await,
+                        Err(broadcast::error::RecvError::Lagged(missed)) => match policy {
+                            LagPolicy::Continue => continue,
+                            LagPolicy::Close => {
+                                collector.emit(Err(Lagged { missed })).await;
+                                break;
+                            }
+                        },
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            }
+        })
+    }
+
     /// Get the number of subscribers
     pub fn subscriber_count(&self) -> usize {
-        self.tx.receiver_count()
+        self.publisher.receiver_count()
     }
 }
 
@@ -52,6 +153,8 @@ where
 {
     tx: watch::Sender<T>,
     rx: watch::Receiver<T>,
+    history: Option<Arc<Mutex<VecDeque<T>>>>,
+    history_capacity: usize,
 }
 
 impl<T> StateFlow<T>
@@ -61,14 +164,69 @@ where
     /// Create a new StateFlow with an initial value
     pub fn new(initial: T) -> Self {
         let (tx, rx) = watch::channel(initial);
-        Self { tx, rx }
+        Self {
+            tx,
+            rx,
+            history: None,
+            history_capacity: 0,
+        }
+    }
+
+    /// Create a new StateFlow that retains up to `capacity` recent values,
+    /// including the initial one
+    ///
+    /// # Example
+    /// ```ignore
+    /// let state = StateFlow::with_history(0, 10);
+    /// state.set(1);
+    /// assert_eq!(state.history(), vec![0, 1]);
+    /// ```
+    pub fn with_history(initial: T, capacity: usize) -> Self {
+        let mut history = VecDeque::with_capacity(capacity);
+        if capacity > 0 {
+            history.push_back(initial.clone());
+        }
+        let (tx, rx) = watch::channel(initial);
+        Self {
+            tx,
+            rx,
+            history: Some(Arc::new(Mutex::new(history))),
+            history_capacity: capacity,
+        }
     }
 
     /// Update the state
     pub fn emit(&self, value: T) {
+        // Held across the send so history and state agree on the order of
+        // concurrent updates
+        let mut history = self
+            .history
+            .as_ref()
+            .map(|history| history.lock().unwrap_or_else(|e| e.into_inner()));
+        if let Some(history) = history.as_mut().filter(|_| self.history_capacity > 0) {
+            if history.len() == self.history_capacity {
+                history.pop_front();
+            }
+            history.push_back(value.clone());
+        }
         let _ = self.tx.send(value);
     }
 
+    /// Get the retained history, oldest first
+    ///
+    /// Always empty unless the StateFlow was created with `with_history`.
+    pub fn history(&self) -> Vec<T> {
+        match &self.history {
+            Some(history) => history
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
     /// Set the state (alias for emit)
     pub fn set(&self, value: T) {
         self.emit(value);
@@ -103,8 +261,420 @@ where
         })
     }
 
+    /// Derive a StateFlow holding `f` applied to this state, like Compose's
+    /// `derivedStateOf`
+    ///
+    /// The derived state is only updated when the selected value changes, so
+    /// updates to unrelated parts of this state are not emitted. Must be
+    /// called within a Tokio runtime; the updating task ends when this
+    /// StateFlow is dropped, or once the derived StateFlow and all collectors
+    /// of it are gone.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let theme = settings.map_state(|s| s.theme.clone());
+    /// ```
+    pub fn map_state<U, F>(&self, f: F) -> StateFlow<U>
+    where
+        U: Clone + Send + Sync + PartialEq + 'static,
+        F: Fn(&T) -> U + Send + Sync + 'static,
+    {
+        let mut rx = self.tx.subscribe();
+        let derived = StateFlow::new(f(&rx.borrow_and_update()));
+        let target = derived.tx.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    changed = rx.changed() => if changed.is_err() { break },
+                    // Nobody can observe the derived state any more
+                    _ = target.closed() => break,
+                }
+                update_derived(&target, f(&rx.borrow_and_update()));
+            }
+        });
+        derived
+    }
+
     /// Get the number of subscribers
     pub fn subscriber_count(&self) -> usize {
         self.tx.receiver_count()
     }
 }
+
+/// Derive a StateFlow from two others, recomputed with `f` whenever either
+/// source changes
+///
+/// The derived state is only updated when the combined value changes. Must
+/// be called within a Tokio runtime; the updating task ends when either
+/// source StateFlow is dropped, or once the derived StateFlow and all
+/// collectors of it are gone.
+///
+/// # Example
+/// ```ignore
+/// let total = combine_states(&price, &quantity, |p, q| p * *q as f64);
+/// ```
+pub fn combine_states<A, B, R, F>(a: &StateFlow<A>, b: &StateFlow<B>, f: F) -> StateFlow<R>
+where
+    A: Clone + Send + Sync + 'static,
+    B: Clone + Send + Sync + 'static,
+    R: Clone + Send + Sync + PartialEq + 'static,
+    F: Fn(&A, &B) -> R + Send + Sync + 'static,
+{
+    let mut rx_a = a.tx.subscribe();
+    let mut rx_b = b.tx.subscribe();
+    let derived = StateFlow::new(f(&rx_a.borrow_and_update(), &rx_b.borrow_and_update()));
+    let target = derived.tx.clone();
+    tokio::spawn(async move {
+        loop {
+            let changed = tokio::select! {
+                changed = rx_a.changed() => changed,
+                changed = rx_b.changed() => changed,
+                // Nobody can observe the derived state any more
+                _ = target.closed() => break,
+            };
+            if changed.is_err() {
+                break;
+            }
+            let next = f(&rx_a.borrow_and_update(), &rx_b.borrow_and_update());
+            update_derived(&target, next);
+        }
+    });
+    derived
+}
+
+/// Store `next` in a derived state, notifying collectors only if it changed
+fn update_derived<T: PartialEq>(target: &watch::Sender<T>, next: T) {
+    target.send_if_modified(|current| {
+        if *current == next {
+            return false;
+        }
+        *current = next;
+        true
+    });
+}
+
+/// A multicast flow that starts its upstream only when `connect` is called
+///
+/// Subscribers attach with `as_flow()` before connecting, so none of them
+/// miss values emitted early on. The upstream waits for the slowest
+/// subscriber, so every subscriber receives every value; a subscriber that
+/// is attached but never collected stalls the upstream until it is dropped.
+pub struct ConnectableFlow<T>
+where
+    T: Clone + Send + 'static,
+{
+    upstream: Flow<T>,
+    /// `None` marks upstream completion
+    tx: Publisher<Option<T>>,
+}
+
+impl<T> ConnectableFlow<T>
+where
+    T: Clone + Send + 'static,
+{
+    /// Wrap an upstream flow; nothing runs until `connect` is called
+    pub fn new(upstream: Flow<T>) -> Self {
+        Self {
+            upstream,
+            tx: Publisher::new(PUBLISH_BUFFER),
+        }
+    }
+
+    /// Attach a subscriber
+    ///
+    /// The subscription is taken when `as_flow()` is called, so the first
+    /// collection sees every value emitted after `connect`. Later
+    /// collections of the same flow subscribe when `collect` starts.
+    pub fn as_flow(&self) -> Flow<T> {
+        let tx = self.tx.clone();
+        let first = Arc::new(Mutex::new(Some(tx.subscribe())));
+        Flow::new(move |collector| {
+            let taken = first.lock().unwrap().take();
+            let mut rx = taken.unwrap_or_else(|| tx.subscribe());
+            async move {
+                loop {
+                    match rx.recv().await {
+                        Ok(Some(value)) => collector.emit(value).await,
+                        Ok(None) | Err(broadcast::error::RecvError::Closed) => break,
+                        Err(broadcast::error::RecvError::Lagged(_)) => {
+                            unreachable!("connected upstream waits for every subscriber")
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Start collecting the upstream and multicasting its values
+    ///
+    /// Each call runs the upstream once more. The returned handle completes
+    /// when the upstream does and can be used to abort it.
+    pub fn connect(&self) -> JoinHandle<()> {
+        let upstream = self.upstream.clone();
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            let values_tx = tx.clone();
+            upstream
+                .collect(move |value| {
+                    let tx = values_tx.clone();
+                    async move {
+                        tx.send(Some(value)).await;
+                    }
+                })
+                .await;
+            tx.send(None).await;
+        })
+    }
+
+    /// Get the number of attached subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::FlowExt;
+    use crate::terminal::FlowTerminal;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_emit_suspending_waits_for_subscriber() {
+        let shared = SharedFlow::<i32>::new(4);
+
+        let producer = tokio::spawn({
+            let shared = shared.clone();
+            async move {
+                shared.emit_suspending(7).await;
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(!producer.is_finished());
+
+        let mut rx = shared.publisher.subscribe();
+        producer.await.unwrap();
+        assert_eq!(rx.recv().await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_emit_suspending_is_woken_by_collector() {
+        let shared = SharedFlow::<i32>::new(1);
+        let collector = tokio::spawn(shared.as_flow().take(500).to_vec());
+        while shared.subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        // Each value waits for the previous one to be taken; polling every
+        // millisecond would need at least half a second
+        tokio::time::timeout(Duration::from_millis(250), async {
+            for value in 0..500 {
+                shared.emit_suspending(value).await;
+            }
+        })
+        .await
+        .expect("emitter should be woken by the collector");
+        assert_eq!(collector.await.unwrap(), (0..500).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_as_flow_subscribes_on_collect() {
+        let shared = SharedFlow::<i32>::new(16);
+
+        let flow = shared.as_flow();
+        assert_eq!(shared.subscriber_count(), 0);
+        shared.emit(1); // Before collect: not delivered
+
+        let collector = tokio::spawn(async move { flow.take(2).to_vec().await });
+        while shared.subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        shared.emit(2);
+        shared.emit(3);
+
+        assert_eq!(collector.await.unwrap(), vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_lag_policy() {
+        let shared = SharedFlow::<i32>::new(2);
+
+        let skipping = shared.as_flow_with_lag_policy(LagPolicy::Continue);
+        let collector = tokio::spawn(async move { skipping.take(2).to_vec().await });
+        while shared.subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        // The collector has not run yet, so only the last two values survive
+        for value in 0..6 {
+            shared.emit(value);
+        }
+        assert_eq!(collector.await.unwrap(), vec![Ok(4), Ok(5)]);
+
+        let closing = shared.as_flow_with_lag_policy(LagPolicy::Close);
+        let collector = tokio::spawn(async move { closing.to_vec().await });
+        while shared.subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        for value in 0..6 {
+            shared.emit(value);
+        }
+        // Completes after reporting the lag, although the emitter is still open
+        assert_eq!(collector.await.unwrap(), vec![Err(Lagged { missed: 4 })]);
+    }
+
+    #[tokio::test]
+    async fn test_state_flow_history() {
+        let state = StateFlow::with_history(0, 3);
+        assert_eq!(state.history(), vec![0]);
+
+        for value in 1..=4 {
+            state.emit(value);
+        }
+        assert_eq!(state.history(), vec![2, 3, 4]);
+        assert_eq!(state.get(), 4);
+
+        assert!(StateFlow::new(0).history().is_empty());
+    }
+
+    #[test]
+    fn test_state_flow_history_matches_state_under_concurrent_emits() {
+        for _ in 0..20 {
+            let state = StateFlow::with_history(0, 4);
+            let emitters: Vec<_> = (1..=4)
+                .map(|id| {
+                    let state = state.clone();
+                    std::thread::spawn(move || {
+                        for value in 0..100 {
+                            state.emit(id * 1000 + value);
+                        }
+                    })
+                })
+                .collect();
+            for emitter in emitters {
+                emitter.join().unwrap();
+            }
+            assert_eq!(state.history().last(), Some(&state.get()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connectable_flow_waits_for_connect() {
+        let numbers = crate::flow::flow(|c| async move {
+            for i in 1..=3 {
+                c.emit(i).await;
+            }
+        });
+        let published = numbers.publish();
+
+        let first = tokio::spawn(published.as_flow().to_vec());
+        let second = tokio::spawn(published.as_flow().to_vec());
+        assert_eq!(published.subscriber_count(), 2);
+
+        published.connect().await.unwrap();
+
+        assert_eq!(first.await.unwrap(), vec![1, 2, 3]);
+        assert_eq!(second.await.unwrap(), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_connectable_flow_delivers_more_than_its_buffer() {
+        let count = PUBLISH_BUFFER as i32 * 10;
+        let published = crate::builders::flow_range(0..count).publish();
+
+        let fast = tokio::spawn(published.as_flow().to_vec());
+        let slow = tokio::spawn(
+            published
+                .as_flow()
+                .map(|value| async move {
+                    tokio::task::yield_now().await;
+                    value
+                })
+                .to_vec(),
+        );
+
+        published.connect().await.unwrap();
+
+        let expected: Vec<i32> = (0..count).collect();
+        assert_eq!(fast.await.unwrap(), expected);
+        assert_eq!(slow.await.unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_map_state_task_ends_with_derived_state() {
+        let count = StateFlow::new(1);
+        let doubled = count.map_state(|n| n * 2);
+        assert_eq!(count.subscriber_count(), 2);
+
+        count.set(2);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(doubled.get(), 4);
+
+        drop(doubled);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(count.subscriber_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_map_state_ignores_unrelated_changes() {
+        #[derive(Clone, PartialEq)]
+        struct Settings {
+            theme: &'static str,
+            volume: u8,
+        }
+
+        let settings = StateFlow::new(Settings {
+            theme: "dark",
+            volume: 5,
+        });
+        let theme = settings.map_state(|s| s.theme);
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        let theme_flow = theme.as_flow();
+        let observer = tokio::spawn(async move {
+            theme_flow
+                .collect(move |value| {
+                    let seen = Arc::clone(&seen_clone);
+                    async move {
+                        seen.lock().unwrap().push(value);
+                    }
+                })
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        settings.set(Settings {
+            theme: "dark",
+            volume: 9,
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(*seen.lock().unwrap(), vec!["dark"]);
+
+        settings.set(Settings {
+            theme: "light",
+            volume: 9,
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(*seen.lock().unwrap(), vec!["dark", "light"]);
+        assert_eq!(theme.get(), "light");
+
+        observer.abort();
+    }
+
+    #[tokio::test]
+    async fn test_combine_states() {
+        let price = StateFlow::new(10);
+        let quantity = StateFlow::new(2);
+        let total = combine_states(&price, &quantity, |p, q| p * q);
+        assert_eq!(total.get(), 20);
+
+        price.set(15);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(total.get(), 30);
+
+        quantity.set(3);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(total.get(), 45);
+    }
+}
@@ -8,12 +8,14 @@ pub mod hot_flow;
 pub mod lifecycle;
 pub mod macros;
 pub mod operators;
+mod publisher;
 pub mod suspending_ext;
+mod task_guard;
 pub mod terminal;
 
-pub use flow::{flow as flow_fn, flow, Flow, FlowCollector};
-pub use hot_flow::{SharedFlow, StateFlow};
-pub use operators::FlowExt;
+pub use flow::{flow as flow_fn, flow, Flow, FlowCollector, ResultCollectorExt};
+pub use hot_flow::{combine_states, ConnectableFlow, LagPolicy, Lagged, SharedFlow, StateFlow};
+pub use operators::{BufferOverflow, DropCounter, DropHook, FlowExt, Notification, TimingOptions};
 pub use suspending_ext::SuspendingExt;
 
 // Terminal operators
@@ -22,24 +24,27 @@ pub use terminal::{FlowError, FlowTerminal};
 // Flow builders
 pub use builders::{
     channel_flow, empty_flow, flow_of, flow_of_one, flow_range, flow_range_inclusive,
-    generate_flow, interval_flow, repeat_flow, IntoFlow,
+    generate_flow, interval_flow, interval_flow_on, lines_flow, produce_flow, repeat_flow,
+    IntoFlow,
 };
 
 // Lifecycle operators
-pub use lifecycle::FlowLifecycle;
+pub use lifecycle::{FlowLifecycle, ResilienceConfig, RetryReason, TerminationReason};
 
 // Combining operators
-pub use combining::{merge, FlowCombining};
+pub use combining::{interleave, merge, merge_sorted, merge_strict, FlowCombining};
 
 // Re-export common items from rs_coroutine_core
 pub use rs_coroutine_core::{
-    get_current_scope, suspend_block, with_current_scope, CancelToken, CoroutineScope, Deferred,
-    Dispatcher, Dispatchers, Executor, JobHandle, Suspending, TokioExecutor, CURRENT_SCOPE,
+    get_current_scope, suspend_block, with_current_scope, yield_now, CancelToken, CoroutineScope,
+    Deferred, Dispatcher, Dispatchers, Executor, JobHandle, Suspending, TokioExecutor,
+    CURRENT_SCOPE,
 };
 
 // Re-export scope module for macros
 pub use rs_coroutine_core::scope;
 
 // Re-export macros (they are already exported via #[macro_export])
-// Macros: flow!, flow_of!, flow_range!, merge!, emit_to!, collect!,
-// launch!, with_context!, coroutine_scope!, async_task!, state_flow!, shared_flow!, flow_ops!
+// Macros: flow!, flow_of!, flow_range!, merge!, emit_to!, collect!, collect_to!,
+// launch!, with_context!, coroutine_scope!, async_task!, state_flow!, shared_flow!, flow_ops!,
+// flow_select!
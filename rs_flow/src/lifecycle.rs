@@ -4,9 +4,52 @@
 //! like start, completion, and errors.
 
 use crate::flow::{Flow, FlowCollector};
+use rs_coroutine_core::panic_message;
 use std::future::Future;
 use std::sync::Arc;
 
+/// Why a collection attempt is being considered for retry by `retry_if`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetryReason {
+    /// The upstream completed without emitting any values
+    Empty,
+    /// The upstream panicked with the given message
+    Panic(String),
+}
+
+/// How a collection ended, reported by `on_terminate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationReason {
+    /// The upstream ran to completion
+    Completed,
+    /// The collection was dropped before the upstream completed,
+    /// e.g. by a downstream `take`
+    Cancelled,
+}
+
+/// Settings for `resilient`
+pub struct ResilienceConfig<T> {
+    /// How many times a failed attempt is re-collected
+    pub max_retries: usize,
+    /// Delay before the first retry, doubled for each further retry
+    pub backoff: std::time::Duration,
+    /// Time limit for a single attempt; an attempt that exceeds it counts as failed
+    pub timeout: Option<std::time::Duration>,
+    /// Flow collected in place of the upstream once all retries have failed
+    pub fallback: Option<Flow<T>>,
+}
+
+impl<T> Default for ResilienceConfig<T> {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            backoff: std::time::Duration::from_millis(100),
+            timeout: None,
+            fallback: None,
+        }
+    }
+}
+
 /// Lifecycle operators for Flow
 pub trait FlowLifecycle<T>: Sized
 where
@@ -26,6 +69,18 @@ where
         F: FnOnce(FlowCollector<T>) -> Fut + Send + Sync + 'static + Clone,
         Fut: Future<Output = ()> + Send + 'static;
 
+    /// Run `check` when collection starts; if it returns `false` the flow
+    /// completes empty without collecting the upstream.
+    ///
+    /// # Example
+    /// ```ignore
+    /// account_events.guard(|| async { session.is_authorized().await })
+    /// ```
+    fn guard<F, Fut>(self, check: F) -> Flow<T>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + Send + 'static;
+
     /// Execute an action when flow completes (successfully or with error).
     /// The action receives `None` on success, or `Some(error)` on failure.
     /// The action can emit values.
@@ -61,6 +116,21 @@ where
         F: FnOnce(FlowCollector<T>) -> Fut + Send + Sync + 'static + Clone,
         Fut: Future<Output = ()> + Send + 'static;
 
+    /// Execute an action once when collection ends, reporting whether the
+    /// upstream completed or the collection was dropped before it did.
+    ///
+    /// # Example
+    /// ```ignore
+    /// flow.on_terminate(|reason| {
+    ///     if reason == TerminationReason::Cancelled {
+    ///         log_truncated();
+    ///     }
+    /// })
+    /// ```
+    fn on_terminate<F>(self, action: F) -> Flow<T>
+    where
+        F: Fn(TerminationReason) + Send + Sync + 'static;
+
     /// Catch and handle errors from upstream.
     /// The handler can emit recovery values or re-throw.
     ///
@@ -78,6 +148,28 @@ where
         F: FnOnce(FlowCollector<T>, String) -> Fut + Send + Sync + 'static + Clone,
         Fut: Future<Output = ()> + Send + 'static;
 
+    /// Recover from a panic in the upstream flow with an async handler.
+    /// The handler receives the panic message; its `Some` value is emitted,
+    /// while `None` completes the flow.
+    ///
+    /// # Example
+    /// ```ignore
+    /// prices.recover_async(|_| async move { fetch_cached_price().await })
+    /// ```
+    fn recover_async<F, Fut>(self, handler: F) -> Flow<T>
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<T>> + Send + 'static;
+
+    /// Wrap each value in `Ok` and turn a panic in the upstream flow into a
+    /// final `Err` carrying the panic message, so failures can be handled as data.
+    ///
+    /// # Example
+    /// ```ignore
+    /// jobs.catch_into_result().collect(|result| async move { report(result) })
+    /// ```
+    fn catch_into_result(self) -> Flow<Result<T, String>>;
+
     /// Retry the flow on failure up to `max_retries` times.
     ///
     /// # Example
@@ -88,6 +180,27 @@ where
     where
         Self: Clone;
 
+    /// Re-collect the flow up to `max_retries` times if it completes without
+    /// emitting any values. Values are passed through unchanged otherwise.
+    ///
+    /// # Example
+    /// ```ignore
+    /// flow.retry_on_empty(3) // Re-run an empty upstream up to 3 more times
+    /// ```
+    fn retry_on_empty(self, max_retries: usize) -> Flow<T>;
+
+    /// Re-collect the flow up to `max_retries` times when it panics or
+    /// completes empty, as long as `should_retry` accepts the reason.
+    /// A panic that is not retried is propagated.
+    ///
+    /// # Example
+    /// ```ignore
+    /// flow.retry_if(3, |reason| matches!(reason, RetryReason::Empty))
+    /// ```
+    fn retry_if<F>(self, max_retries: usize, should_retry: F) -> Flow<T>
+    where
+        F: Fn(RetryReason) -> bool + Send + Sync + 'static;
+
     /// Timeout if no values are emitted within the specified duration.
     /// Returns a flow that completes with an error if timeout occurs.
     ///
@@ -96,6 +209,22 @@ where
     /// flow.timeout(Duration::from_secs(5))
     /// ```
     fn with_timeout(self, duration: std::time::Duration) -> Flow<T>;
+
+    /// Apply a per-attempt timeout, retry panicking or timed-out attempts with
+    /// exponential backoff, and switch to the fallback once retries run out.
+    /// Without a fallback, the last panic is propagated and a final timeout
+    /// completes the flow. Values emitted by failed attempts are kept.
+    ///
+    /// # Example
+    /// ```ignore
+    /// flow.resilient(ResilienceConfig {
+    ///     max_retries: 3,
+    ///     timeout: Some(Duration::from_secs(5)),
+    ///     fallback: Some(cached_values()),
+    ///     ..Default::default()
+    /// })
+    /// ```
+    fn resilient(self, config: ResilienceConfig<T>) -> Flow<T>;
 }
 
 impl<T> FlowLifecycle<T> for Flow<T>
@@ -127,6 +256,31 @@ where
         })
     }
 
+    fn guard<F, Fut>(self, check: F) -> Flow<T>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + Send + 'static,
+    {
+        let check = Arc::new(check);
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let check = Arc::clone(&check);
+            async move {
+                if !check().await {
+                    return;
+                }
+                upstream
+                    .collect(move |value| {
+                        let collector = collector.clone();
+                        async move {
+                            collector.emit(value).await;
+                        }
+                    })
+                    .await;
+            }
+        })
+    }
+
     fn on_completion<F, Fut>(self, action: F) -> Flow<T>
     where
         F: FnOnce(FlowCollector<T>, Option<Box<dyn std::error::Error + Send + Sync>>) -> Fut
@@ -190,6 +344,33 @@ where
         })
     }
 
+    fn on_terminate<F>(self, action: F) -> Flow<T>
+    where
+        F: Fn(TerminationReason) + Send + Sync + 'static,
+    {
+        let action: Arc<dyn Fn(TerminationReason) + Send + Sync> = Arc::new(action);
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let mut guard = TerminationGuard {
+                action: Some(Arc::clone(&action)),
+            };
+            async move {
+                upstream
+                    .collect(move |value| {
+                        let collector = collector.clone();
+                        async move {
+                            collector.emit(value).await;
+                        }
+                    })
+                    .await;
+
+                if let Some(action) = guard.action.take() {
+                    action(TerminationReason::Completed);
+                }
+            }
+        })
+    }
+
     fn catch_panic<F, Fut>(self, handler: F) -> Flow<T>
     where
         F: FnOnce(FlowCollector<T>, String) -> Fut + Send + Sync + 'static + Clone,
@@ -216,20 +397,69 @@ where
                 match futures::FutureExt::catch_unwind(result).await {
                     Ok(()) => {}
                     Err(panic) => {
-                        let panic_msg = if let Some(s) = panic.downcast_ref::<&str>() {
-                            s.to_string()
-                        } else if let Some(s) = panic.downcast_ref::<String>() {
-                            s.clone()
-                        } else {
-                            "Unknown panic".to_string()
-                        };
-                        handler(collector, panic_msg).await;
+                        handler(collector, panic_message(panic.as_ref())).await;
+                    }
+                }
+            }
+        })
+    }
+
+    fn recover_async<F, Fut>(self, handler: F) -> Flow<T>
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<T>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let handler = Arc::clone(&handler);
+            async move {
+                let collector_clone = collector.clone();
+
+                let result = std::panic::AssertUnwindSafe(async {
+                    upstream
+                        .collect(move |value| {
+                            let collector = collector_clone.clone();
+                            async move {
+                                collector.emit(value).await;
+                            }
+                        })
+                        .await;
+                });
+
+                if let Err(panic) = futures::FutureExt::catch_unwind(result).await {
+                    if let Some(value) = handler(panic_message(panic.as_ref())).await {
+                        collector.emit(value).await;
                     }
                 }
             }
         })
     }
 
+    fn catch_into_result(self) -> Flow<Result<T, String>> {
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            async move {
+                let collector_clone = collector.clone();
+
+                let result = std::panic::AssertUnwindSafe(async {
+                    upstream
+                        .collect(move |value| {
+                            let collector = collector_clone.clone();
+                            async move {
+                                collector.emit(Ok(value)).await;
+                            }
+                        })
+                        .await;
+                });
+
+                if let Err(panic) = futures::FutureExt::catch_unwind(result).await {
+                    collector.emit(Err(panic_message(panic.as_ref()))).await;
+                }
+            }
+        })
+    }
+
     fn retry(self, max_retries: usize) -> Flow<T>
     where
         Self: Clone,
@@ -269,6 +499,87 @@ where
         })
     }
 
+    fn retry_on_empty(self, max_retries: usize) -> Flow<T> {
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            async move {
+                let emitted = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+                for _ in 0..=max_retries {
+                    let collector = collector.clone();
+                    let emitted_clone = Arc::clone(&emitted);
+
+                    upstream
+                        .collect(move |value| {
+                            let collector = collector.clone();
+                            let emitted = Arc::clone(&emitted_clone);
+                            async move {
+                                emitted.store(true, std::sync::atomic::Ordering::SeqCst);
+                                collector.emit(value).await;
+                            }
+                        })
+                        .await;
+
+                    if emitted.load(std::sync::atomic::Ordering::SeqCst) {
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    fn retry_if<F>(self, max_retries: usize, should_retry: F) -> Flow<T>
+    where
+        F: Fn(RetryReason) -> bool + Send + Sync + 'static,
+    {
+        let should_retry = Arc::new(should_retry);
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let should_retry = Arc::clone(&should_retry);
+            async move {
+                let mut attempts = 0;
+
+                loop {
+                    let emitted = Arc::new(std::sync::atomic::AtomicBool::new(false));
+                    let emitted_clone = Arc::clone(&emitted);
+                    let collector_clone = collector.clone();
+                    let upstream_clone = upstream.clone();
+
+                    let result = std::panic::AssertUnwindSafe(async {
+                        upstream_clone
+                            .collect(move |value| {
+                                let collector = collector_clone.clone();
+                                let emitted = Arc::clone(&emitted_clone);
+                                async move {
+                                    emitted.store(true, std::sync::atomic::Ordering::SeqCst);
+                                    collector.emit(value).await;
+                                }
+                            })
+                            .await;
+                    });
+
+                    let (reason, panic) = match futures::FutureExt::catch_unwind(result).await {
+                        Ok(()) if emitted.load(std::sync::atomic::Ordering::SeqCst) => break,
+                        Ok(()) => (RetryReason::Empty, None),
+                        Err(panic) => (
+                            RetryReason::Panic(panic_message(panic.as_ref())),
+                            Some(panic),
+                        ),
+                    };
+
+                    if attempts < max_retries && should_retry(reason) {
+                        attempts += 1;
+                        continue;
+                    }
+                    if let Some(panic) = panic {
+                        std::panic::resume_unwind(panic); // Re-throw
+                    }
+                    break;
+                }
+            }
+        })
+    }
+
     fn with_timeout(self, duration: std::time::Duration) -> Flow<T> {
         Flow::new(move |collector| {
             let upstream = self.clone();
@@ -293,11 +604,84 @@ where
             }
         })
     }
+
+    fn resilient(self, config: ResilienceConfig<T>) -> Flow<T> {
+        let config = Arc::new(config);
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let config = Arc::clone(&config);
+            async move {
+                let mut attempts = 0;
+
+                loop {
+                    let collector_clone = collector.clone();
+                    let upstream_clone = upstream.clone();
+
+                    let attempt =
+                        futures::FutureExt::catch_unwind(std::panic::AssertUnwindSafe(async {
+                            upstream_clone
+                                .collect(move |value| {
+                                    let collector = collector_clone.clone();
+                                    async move {
+                                        collector.emit(value).await;
+                                    }
+                                })
+                                .await;
+                        }));
+                    let panic = match config.timeout {
+                        Some(limit) => match tokio::time::timeout(limit, attempt).await {
+                            Ok(Ok(())) => break, // Success
+                            Ok(Err(panic)) => Some(panic),
+                            Err(_) => None, // Timed out
+                        },
+                        None => match attempt.await {
+                            Ok(()) => break,
+                            Err(panic) => Some(panic),
+                        },
+                    };
+
+                    if attempts < config.max_retries {
+                        tokio::time::sleep(config.backoff * 2u32.saturating_pow(attempts as u32))
+                            .await;
+                        attempts += 1;
+                        continue;
+                    }
+                    if let Some(fallback) = &config.fallback {
+                        fallback
+                            .collect(move |value| {
+                                let collector = collector.clone();
+                                async move {
+                                    collector.emit(value).await;
+                                }
+                            })
+                            .await;
+                    } else if let Some(panic) = panic {
+                        std::panic::resume_unwind(panic); // Re-throw
+                    }
+                    break;
+                }
+            }
+        })
+    }
+}
+
+/// Reports `Cancelled` if dropped before the collection completed
+struct TerminationGuard {
+    action: Option<Arc<dyn Fn(TerminationReason) + Send + Sync>>,
+}
+
+impl Drop for TerminationGuard {
+    fn drop(&mut self) {
+        if let Some(action) = self.action.take() {
+            action(TerminationReason::Cancelled);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::builders::flow_of;
     use crate::flow::flow;
     use crate::terminal::FlowTerminal;
     use std::time::Duration;
@@ -316,6 +700,26 @@ mod tests {
         assert_eq!(result, vec![1, 2, 3]);
     }
 
+    #[tokio::test]
+    async fn test_guard() {
+        let started = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let started_clone = Arc::clone(&started);
+        let source = flow(move |c| {
+            started_clone.store(true, std::sync::atomic::Ordering::SeqCst);
+            async move {
+                c.emit(1).await;
+                c.emit(2).await;
+            }
+        });
+
+        let denied = source.clone().guard(|| async { false }).to_vec().await;
+        assert!(denied.is_empty());
+        assert!(!started.load(std::sync::atomic::Ordering::SeqCst));
+
+        let allowed = source.guard(|| async { true }).to_vec().await;
+        assert_eq!(allowed, vec![1, 2]);
+    }
+
     #[tokio::test]
     async fn test_on_completion() {
         let flow = flow(|c| async move {
@@ -354,6 +758,92 @@ mod tests {
         assert_eq!(result, vec![1]);
     }
 
+    #[tokio::test]
+    async fn test_retry_on_empty() {
+        let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let runs_clone = Arc::clone(&runs);
+        let flow = flow(move |c| {
+            let run = runs_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async move {
+                if run > 0 {
+                    c.emit(1).await;
+                    c.emit(2).await;
+                }
+            }
+        })
+        .retry_on_empty(3);
+
+        let result = flow.to_vec().await;
+        assert_eq!(result, vec![1, 2]);
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    /// A flow that panics on its first `panics` runs, is empty for the next
+    /// `empties` runs and emits `[1]` afterwards
+    fn flaky_flow(
+        panics: usize,
+        empties: usize,
+    ) -> (Flow<i32>, Arc<std::sync::atomic::AtomicUsize>) {
+        let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let runs_clone = Arc::clone(&runs);
+        let flow = flow(move |c| {
+            let run = runs_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async move {
+                if run < panics {
+                    panic!("attempt {} failed", run);
+                }
+                if run >= panics + empties {
+                    c.emit(1).await;
+                }
+            }
+        });
+        (flow, runs)
+    }
+
+    #[tokio::test]
+    async fn test_retry_if_empty() {
+        let (flow, runs) = flaky_flow(0, 2);
+        let result = flow
+            .retry_if(3, |reason| reason == RetryReason::Empty)
+            .to_vec()
+            .await;
+
+        assert_eq!(result, vec![1]);
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_if_panic() {
+        let (flow, runs) = flaky_flow(2, 0);
+        let result = flow
+            .retry_if(3, |reason| matches!(reason, RetryReason::Panic(_)))
+            .to_vec()
+            .await;
+
+        assert_eq!(result, vec![1]);
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_if_rejected_reason_is_not_retried() {
+        let (flow, runs) = flaky_flow(0, 1);
+        let result = flow
+            .retry_if(3, |reason| matches!(reason, RetryReason::Panic(_)))
+            .to_vec()
+            .await;
+        assert!(result.is_empty());
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let (flow, _) = flaky_flow(1, 0);
+        let panicked = tokio::spawn(async move {
+            flow.retry_if(3, |reason| reason == RetryReason::Empty)
+                .to_vec()
+                .await
+        })
+        .await;
+        assert!(panicked.is_err());
+    }
+
     #[tokio::test]
     async fn test_with_timeout() {
         let flow = flow(|c| async move {
@@ -368,4 +858,115 @@ mod tests {
         let result = flow.to_vec().await;
         assert_eq!(result, vec![1, 2]);
     }
+
+    #[tokio::test]
+    async fn test_on_terminate_completed_and_cancelled() {
+        use crate::operators::FlowExt;
+
+        let reasons = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let reasons_clone = Arc::clone(&reasons);
+        let numbers = flow(|c| async move {
+            for i in 1..=5 {
+                c.emit(i).await;
+            }
+        })
+        .on_terminate(move |reason| reasons_clone.lock().unwrap().push(reason));
+
+        assert_eq!(numbers.clone().to_vec().await, vec![1, 2, 3, 4, 5]);
+        assert_eq!(*reasons.lock().unwrap(), vec![TerminationReason::Completed]);
+
+        assert_eq!(numbers.take(2).to_vec().await, vec![1, 2]);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(
+            *reasons.lock().unwrap(),
+            vec![TerminationReason::Completed, TerminationReason::Cancelled]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resilient_recovers_after_retry() {
+        let (flow, runs) = flaky_flow(1, 0);
+        let result = flow
+            .resilient(ResilienceConfig {
+                max_retries: 2,
+                backoff: Duration::from_millis(1),
+                timeout: Some(Duration::from_millis(100)),
+                fallback: Some(flow_of(vec![99])),
+            })
+            .to_vec()
+            .await;
+
+        assert_eq!(result, vec![1]);
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resilient_falls_back_after_failed_retries() {
+        let (failing, runs) = flaky_flow(usize::MAX, 0);
+        let result = failing
+            .resilient(ResilienceConfig {
+                max_retries: 2,
+                backoff: Duration::from_millis(1),
+                timeout: None,
+                fallback: Some(flow_of(vec![99])),
+            })
+            .to_vec()
+            .await;
+        assert_eq!(result, vec![99]);
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+        let stalled = flow(|c| async move {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            c.emit(1).await;
+        });
+        let result = stalled
+            .resilient(ResilienceConfig {
+                max_retries: 1,
+                backoff: Duration::from_millis(1),
+                timeout: Some(Duration::from_millis(20)),
+                fallback: Some(flow_of(vec![99])),
+            })
+            .to_vec()
+            .await;
+        assert_eq!(result, vec![99]);
+    }
+
+    #[tokio::test]
+    async fn test_recover_async() {
+        let failing = flow(|c| async move {
+            c.emit(1).await;
+            panic!("connection reset");
+        });
+
+        let result = failing
+            .clone()
+            .recover_async(|message| async move {
+                // Simulated fallback fetch
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                (message == "connection reset").then_some(-1)
+            })
+            .to_vec()
+            .await;
+        assert_eq!(result, vec![1, -1]);
+
+        let result = failing.recover_async(|_| async { None }).to_vec().await;
+        assert_eq!(result, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_catch_into_result() {
+        let failing = flow(|c| async move {
+            c.emit(1).await;
+            panic!("boom");
+        });
+        let result = failing.catch_into_result().to_vec().await;
+        assert_eq!(result, vec![Ok(1), Err("boom".to_string())]);
+
+        let healthy = flow(|c| async move {
+            c.emit(1).await;
+            c.emit(2).await;
+        });
+        let result = healthy.catch_into_result().to_vec().await;
+        assert_eq!(result, vec![Ok(1), Ok(2)]);
+    }
 }
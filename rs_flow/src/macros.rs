@@ -4,6 +4,11 @@
 /// and provide a Kotlin-like developer experience.
 /// Creates a Flow with implicit collector access via emit!()
 ///
+/// `emit!` can be used anywhere in the body, including nested blocks and
+/// loops. To emit from spawned sub-tasks, name the collector with the
+/// `|collector|` form, move a clone of it into the task and emit through
+/// the clone with `emit_to!`.
+///
 /// # Example
 /// ```ignore
 /// use coroflow::flow;
@@ -14,9 +19,30 @@
 ///         emit!(i);
 ///     }
 /// };
+///
+/// // Named collector, shared with a spawned task
+/// let numbers = flow! { |collector|
+///     let task = {
+///         let collector = collector.clone();
+///         tokio::spawn(async move { emit_to!(collector, 1); })
+///     };
+///     task.await.unwrap();
+///     emit!(2);
+/// };
 /// ```
 #[macro_export]
 macro_rules! flow {
+    { |$collector:ident| $($body:tt)* } => {{
+        $crate::flow::flow(|$collector| async move {
+            #[allow(unused_macros)]
+            macro_rules! emit {
+                ($value:expr) => {
+                    $collector.emit($value).await
+                };
+            }
+            $($body)*
+        })
+    }};
     { $($body:tt)* } => {{
         $crate::flow::flow(|__collector__| async move {
             // Make collector available for emit! macro
@@ -67,6 +93,29 @@ macro_rules! collect {
     };
 }
 
+/// Collect a flow into the given collection type
+///
+/// Supports `Vec`, `HashSet` and `HashMap` (for flows of key-value pairs).
+///
+/// # Examples
+/// ```ignore
+/// let list = collect_to!(numbers, Vec);
+/// let unique = collect_to!(numbers, HashSet);
+/// let by_id = collect_to!(users.map_sync(|u| (u.id, u)), HashMap);
+/// ```
+#[macro_export]
+macro_rules! collect_to {
+    ($flow:expr, Vec) => {
+        $crate::FlowTerminal::to_vec($flow).await
+    };
+    ($flow:expr, HashSet) => {
+        $crate::FlowTerminal::to_set($flow).await
+    };
+    ($flow:expr, HashMap) => {
+        $flow.to_map().await
+    };
+}
+
 /// Launch a coroutine on the current scope
 ///
 /// # Examples
@@ -175,6 +224,9 @@ macro_rules! async_task {
 
 /// Create a StateFlow with initial value
 ///
+/// Use the `Type; initial` form when the element type cannot be inferred
+/// from the initial value.
+///
 /// # Example
 /// ```rust
 /// use coroflow::state_flow;
@@ -182,50 +234,142 @@ macro_rules! async_task {
 /// let counter = state_flow!(0);
 /// counter.set(1);
 /// let value = counter.get();
+///
+/// let name = state_flow!(Option<String>; None);
+/// name.set(Some("alice".to_string()));
 /// ```
 #[macro_export]
 macro_rules! state_flow {
-    ($initial:expr) => {
-        $crate::StateFlow::new($initial)
+    // Scan the input for a top-level `;` separating the type annotation
+    (@split [$($ty:tt)+]; $initial:expr) => {
+        $crate::StateFlow::<$($ty)+>::new($initial)
+    };
+    (@split [$($ty:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::state_flow!(@split [$($ty)* $next] $($rest)*)
+    };
+    (@split [$($initial:tt)+]) => {
+        $crate::StateFlow::new($($initial)+)
+    };
+    ($($input:tt)+) => {
+        $crate::state_flow!(@split [] $($input)+)
     };
 }
 
 /// Create a SharedFlow with capacity
 ///
+/// Use the `Type; capacity` form to name the element type up front.
+///
 /// # Example
 /// ```rust
 /// use coroflow::shared_flow;
 ///
 /// let events = shared_flow!(16);
 /// events.emit("event");
+///
+/// let ids = shared_flow!(Option<u64>; 8);
+/// ids.emit(Some(1));
 /// ```
 #[macro_export]
 macro_rules! shared_flow {
-    ($capacity:expr) => {
-        $crate::SharedFlow::new($capacity)
+    // Scan the input for a top-level `;` separating the type annotation
+    (@split [$($ty:tt)+]; $capacity:expr) => {
+        $crate::SharedFlow::<$($ty)+>::new($capacity)
+    };
+    (@split [$($ty:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::shared_flow!(@split [$($ty)* $next] $($rest)*)
+    };
+    (@split [$($capacity:tt)+]) => {
+        $crate::SharedFlow::new($($capacity)+)
+    };
+    ($($input:tt)+) => {
+        $crate::shared_flow!(@split [] $($input)+)
     };
     () => {
         $crate::SharedFlow::new(16)
     };
 }
 
+/// Collect from several flows concurrently, handling each emission with the
+/// arm for its source
+///
+/// Each arm maps its flow's values into a common type, and the arms are merged
+/// into a single flow, so values arrive in the order the sources emit them.
+///
+/// # Example
+/// ```ignore
+/// let events = flow_select! {
+///     clicks => |click| Event::Click(click),
+///     keys => |key| Event::Key(key),
+/// };
+/// events.collect(|event| async move { handle(event) }).await;
+/// ```
+#[macro_export]
+macro_rules! flow_select {
+    { $($flow:expr => |$x:ident| $body:expr),+ $(,)? } => {{
+        $crate::combining::merge(vec![
+            $($crate::FlowExt::map_sync($flow, move |$x| $body)),+
+        ])
+    }};
+}
+
 /// Apply multiple operators to a flow in a pipeline
 ///
+/// Supported operators: `map`, `map_sync`, `filter`, `filter_sync`, `scan`,
+/// `debounce`, `distinct_until_changed`, `merge_with`, `take`, `buffer` and
+/// `flow_on`. `map` and `filter` take closures returning futures; `scan`
+/// takes an initial value and a sync accumulator.
+///
 /// # Example
 /// ```ignore
 /// let result = flow_ops!(numbers => map_sync |x| x * 2, take 3);
+/// let totals = flow_ops!(numbers => map |x| async move { x * 2 }, scan 0, |acc, x| acc + x);
 /// ```
 #[macro_export]
 macro_rules! flow_ops {
-    ($flow:expr => $($op:ident $($args:tt)*),+ $(,)?) => {{
+    ($flow:expr => $($ops:tt)+) => {{
         let __flow__ = $flow;
-        $(
-            let __flow__ = $crate::flow_ops!(@apply __flow__, $op $($args)*);
-        )+
-        __flow__
+        $crate::flow_ops!(@chain __flow__; []; $($ops)+)
     }};
 
+    // Split the operator list on commas, applying each operator in turn.
+    // `scan` is matched whole because its closure arguments contain a comma.
+    (@chain $flow:expr; []; scan $initial:expr, |$acc:ident, $x:ident| $body:expr $(, $($rest:tt)*)?) => {
+        $crate::flow_ops!(
+            @chain $crate::flow_ops!(@apply $flow, scan $initial, |$acc, $x| $body); []; $($($rest)*)?
+        )
+    };
+    (@chain $flow:expr; [$($op:tt)+]; , $($rest:tt)*) => {
+        $crate::flow_ops!(@chain $crate::flow_ops!(@apply $flow, $($op)+); []; $($rest)*)
+    };
+    (@chain $flow:expr; [$($op:tt)+];) => {
+        $crate::flow_ops!(@apply $flow, $($op)+)
+    };
+    (@chain $flow:expr; [];) => {
+        $flow
+    };
+    (@chain $flow:expr; [$($op:tt)*]; $next:tt $($rest:tt)*) => {
+        $crate::flow_ops!(@chain $flow; [$($op)* $next]; $($rest)*)
+    };
+
     // Individual operator applications
+    (@apply $flow:expr, map |$x:ident| $body:expr) => {
+        $crate::FlowExt::map($flow, move |$x| $body)
+    };
+    (@apply $flow:expr, filter |$x:ident| $body:expr) => {
+        $crate::FlowExt::filter($flow, move |$x| $body)
+    };
+    (@apply $flow:expr, scan $initial:expr, |$acc:ident, $x:ident| $body:expr) => {
+        $crate::FlowExt::scan_async($flow, $initial, move |$acc, $x| async move { $body })
+    };
+    (@apply $flow:expr, debounce $timeout:expr) => {
+        $crate::FlowExt::debounce($flow, $timeout)
+    };
+    (@apply $flow:expr, distinct_until_changed) => {
+        $crate::FlowExt::distinct_until_changed($flow)
+    };
+    (@apply $flow:expr, merge_with $other:expr) => {
+        $crate::combining::merge(vec![$flow, $other])
+    };
     (@apply $flow:expr, map_sync |$x:ident| $body:expr) => {
         $crate::FlowExt::map_sync($flow, |$x| $body)
     };
@@ -245,7 +389,7 @@ macro_rules! flow_ops {
 
 #[cfg(test)]
 mod tests {
-    use crate::{flow_fn, Flow, FlowExt, StateFlow};
+    use crate::{flow_fn, Flow, FlowExt, FlowTerminal, SharedFlow, StateFlow};
     use std::sync::Arc;
     use tokio::sync::Mutex;
 
@@ -336,4 +480,178 @@ mod tests {
         let result = collected.lock().await;
         assert_eq!(*result, vec![1, 2, 3]);
     }
+
+    #[tokio::test]
+    async fn test_flow_select_invokes_arm_per_source() {
+        #[derive(Debug, PartialEq)]
+        enum Event {
+            Number(i32),
+            Word(&'static str),
+        }
+
+        let numbers = flow_fn(|collector| async move {
+            collector.emit(1).await;
+            collector.emit(2).await;
+        });
+        let words = flow_fn(|collector| async move {
+            collector.emit("a").await;
+            collector.emit("b").await;
+        });
+
+        let events = flow_select! {
+            numbers => |n| Event::Number(n * 10),
+            words => |w| Event::Word(w),
+        }
+        .to_vec()
+        .await;
+
+        assert_eq!(events.len(), 4);
+        let numbers: Vec<_> = events
+            .iter()
+            .filter(|e| matches!(e, Event::Number(_)))
+            .collect();
+        let words: Vec<_> = events
+            .iter()
+            .filter(|e| matches!(e, Event::Word(_)))
+            .collect();
+        assert_eq!(numbers, vec![&Event::Number(10), &Event::Number(20)]);
+        assert_eq!(words, vec![&Event::Word("a"), &Event::Word("b")]);
+    }
+
+    #[tokio::test]
+    async fn test_emit_in_nested_blocks() {
+        let numbers: Flow<i32> = flow! {
+            let enabled = true;
+            if enabled {
+                for i in 1..=3 {
+                    if i != 2 {
+                        emit!(i);
+                    }
+                }
+            }
+        };
+
+        assert_eq!(numbers.to_vec().await, vec![1, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_emit_from_spawned_subtask() {
+        let numbers: Flow<i32> = flow! { |collector|
+            emit!(1);
+            let task = {
+                let collector = collector.clone();
+                tokio::spawn(async move {
+                    for i in 2..=3 {
+                        emit_to!(collector, i);
+                    }
+                })
+            };
+            task.await.unwrap();
+            emit!(4);
+        };
+
+        assert_eq!(numbers.to_vec().await, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_collect_to_vec() {
+        let numbers: Flow<i32> = flow! {
+            for i in [3, 1, 3] {
+                emit!(i);
+            }
+        };
+
+        let list = collect_to!(numbers, Vec);
+        assert_eq!(list, vec![3, 1, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_collect_to_hash_set() {
+        let numbers: Flow<i32> = flow! {
+            for i in [3, 1, 3] {
+                emit!(i);
+            }
+        };
+
+        let set = collect_to!(numbers, HashSet);
+        assert_eq!(set, [1, 3].into_iter().collect());
+    }
+
+    #[tokio::test]
+    async fn test_collect_to_hash_map() {
+        let pairs: Flow<(&str, i32)> = flow! {
+            emit!(("a", 1));
+            emit!(("b", 2));
+            emit!(("a", 3));
+        };
+
+        let map = collect_to!(pairs, HashMap);
+        assert_eq!(map, [("a", 3), ("b", 2)].into_iter().collect());
+    }
+
+    #[tokio::test]
+    async fn test_flow_ops_async_pipeline() {
+        let numbers: Flow<i32> = flow! {
+            for i in [1, 1, 2, 3] {
+                emit!(i);
+            }
+        };
+        let extra: Flow<i32> = flow! {
+            emit!(10);
+        };
+
+        let mut result = flow_ops!(numbers =>
+            distinct_until_changed,
+            map |x| async move { x * 2 },
+            filter |x| { let keep = *x > 2; async move { keep } },
+            scan 0, |acc, x| acc + x,
+            merge_with extra,
+        )
+        .to_vec()
+        .await;
+        result.sort();
+
+        assert_eq!(result, vec![0, 4, 10, 10]);
+    }
+
+    #[tokio::test]
+    async fn test_flow_ops_debounce() {
+        let numbers: Flow<i32> = flow! {
+            for i in 1..=3 {
+                emit!(i);
+            }
+        };
+
+        let result = flow_ops!(numbers =>
+            map |x| async move { x * 10 },
+            debounce std::time::Duration::from_millis(20),
+        )
+        .to_vec()
+        .await;
+
+        assert_eq!(result, vec![30]);
+    }
+
+    #[tokio::test]
+    async fn test_typed_state_and_shared_flow() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct User {
+            name: String,
+        }
+
+        let current_user: StateFlow<Option<User>> = state_flow!(Option<User>; None);
+        assert_eq!(current_user.get(), None);
+
+        let alice = User {
+            name: "alice".to_string(),
+        };
+        current_user.set(Some(alice.clone()));
+        assert_eq!(current_user.get(), Some(alice));
+
+        let logins: SharedFlow<Option<User>> = shared_flow!(Option<User>; 4);
+        assert_eq!(logins.subscriber_count(), 0);
+
+        let counter = state_flow!(1 + 1);
+        assert_eq!(counter.get(), 2);
+    }
 }
@@ -0,0 +1,193 @@
+//! Buffering and backpressure operators
+//!
+//! The `FlowExt` implementation delegates these operators here.
+
+use super::{BufferOverflow, DropCounter, DropHook};
+use crate::flow::Flow;
+use crate::task_guard::AbortOnDrop;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, Notify};
+
+pub(super) fn buffer<T>(upstream: Flow<T>, capacity: usize) -> Flow<T>
+where
+    T: Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let (tx, rx) = mpsc::channel(capacity);
+
+            let (producer, _guard) = AbortOnDrop::wrap(async move {
+                upstream.send_to(tx).await;
+            });
+            let producer = tokio::spawn(producer);
+
+            collector.emit_all(rx).await;
+
+            let _ = producer.await;
+        }
+    })
+}
+
+pub(super) fn on_backpressure_drop<T>(upstream: Flow<T>) -> (Flow<T>, DropCounter)
+where
+    T: Send + 'static,
+{
+    let dropped = DropCounter::default();
+    let counter = dropped.clone();
+    let flow = Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let counter = counter.clone();
+        async move {
+            // A single slot: values arriving while it is taken are dropped
+            let (tx, mut rx) = mpsc::channel::<T>(1);
+
+            let (producer, _guard) = AbortOnDrop::wrap(async move {
+                upstream
+                    .collect(move |value| {
+                        if tx.try_send(value).is_err() {
+                            counter.increment();
+                        }
+                        async {}
+                    })
+                    .await;
+            });
+            let producer = tokio::spawn(producer);
+
+            while let Some(value) = rx.recv().await {
+                collector.emit(value).await;
+            }
+
+            let _ = producer.await;
+        }
+    });
+    (flow, dropped)
+}
+
+pub(super) fn on_backpressure_latest<T>(upstream: Flow<T>) -> (Flow<T>, DropCounter)
+where
+    T: Send + 'static,
+{
+    let dropped = DropCounter::default();
+    let counter = dropped.clone();
+    let flow = Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let counter = counter.clone();
+        async move {
+            // Latest pending value and whether the upstream has completed
+            let slot = Arc::new(Mutex::new((None::<T>, false)));
+            let notify = Arc::new(Notify::new());
+
+            let (producer, _guard) = AbortOnDrop::wrap({
+                let slot = Arc::clone(&slot);
+                let notify = Arc::clone(&notify);
+                async move {
+                    let values_slot = Arc::clone(&slot);
+                    let values_notify = Arc::clone(&notify);
+                    upstream
+                        .collect(move |value| {
+                            if values_slot.lock().unwrap().0.replace(value).is_some() {
+                                counter.increment();
+                            }
+                            values_notify.notify_one();
+                            async {}
+                        })
+                        .await;
+                    slot.lock().unwrap().1 = true;
+                    notify.notify_one();
+                }
+            });
+            let producer = tokio::spawn(producer);
+
+            loop {
+                let (value, done) = {
+                    let mut slot = slot.lock().unwrap();
+                    (slot.0.take(), slot.1)
+                };
+                match value {
+                    Some(value) => collector.emit(value).await,
+                    None if done => break,
+                    None => notify.notified().await,
+                }
+            }
+
+            let _ = producer.await;
+        }
+    });
+    (flow, dropped)
+}
+
+pub(super) fn buffer_with<T>(
+    upstream: Flow<T>,
+    capacity: usize,
+    overflow: BufferOverflow,
+    on_drop: Option<DropHook<T>>,
+) -> Flow<T>
+where
+    T: Send + 'static,
+{
+    if overflow == BufferOverflow::Suspend {
+        // Nothing is ever dropped, so the hook is never called
+        return buffer(upstream, capacity);
+    }
+    let capacity = capacity.max(1);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let on_drop = on_drop.clone();
+        async move {
+            // Buffered values and whether the upstream has completed
+            let slot = Arc::new(Mutex::new((VecDeque::<T>::new(), false)));
+            let notify = Arc::new(Notify::new());
+
+            let (producer, _guard) = AbortOnDrop::wrap({
+                let slot = Arc::clone(&slot);
+                let notify = Arc::clone(&notify);
+                async move {
+                    let values_slot = Arc::clone(&slot);
+                    let values_notify = Arc::clone(&notify);
+                    upstream
+                        .collect(move |value| {
+                            let dropped = {
+                                let mut slot = values_slot.lock().unwrap();
+                                let buffered = &mut slot.0;
+                                if buffered.len() < capacity {
+                                    buffered.push_back(value);
+                                    None
+                                } else if overflow == BufferOverflow::DropOldest {
+                                    let oldest = buffered.pop_front();
+                                    buffered.push_back(value);
+                                    oldest
+                                } else {
+                                    Some(value)
+                                }
+                            };
+                            if let (Some(value), Some(on_drop)) = (&dropped, &on_drop) {
+                                on_drop(value);
+                            }
+                            values_notify.notify_one();
+                            async {}
+                        })
+                        .await;
+                    slot.lock().unwrap().1 = true;
+                    notify.notify_one();
+                }
+            });
+            let producer = tokio::spawn(producer);
+
+            loop {
+                let (value, done) = {
+                    let mut slot = slot.lock().unwrap();
+                    (slot.0.pop_front(), slot.1)
+                };
+                match value {
+                    Some(value) => collector.emit(value).await,
+                    None if done => break,
+                    None => notify.notified().await,
+                }
+            }
+
+            let _ = producer.await;
+        }
+    })
+}
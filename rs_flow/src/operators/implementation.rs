@@ -1,8 +1,12 @@
 use super::*;
+use crate::lifecycle::FlowLifecycle;
+use crate::task_guard::AbortOnDrop;
 use std::future::Future;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify, Semaphore};
+use tokio::task::JoinHandle;
 
 impl<T> FlowExt<T> for Flow<T>
 where
@@ -59,6 +63,23 @@ where
         })
     }
 
+    fn filter_map<U, F>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Option<U> + Send + Sync + 'static,
+    {
+        transform::filter_map(self, f)
+    }
+
+    fn filter_map_async<U, F, Fut>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<U>> + Send + 'static,
+    {
+        transform::filter_map_async(self, f)
+    }
+
     fn take(self, count: usize) -> Flow<T> {
         Flow::new(move |collector| {
             let upstream = self.clone();
@@ -110,29 +131,16 @@ where
     }
 
     fn buffer(self, capacity: usize) -> Flow<T> {
-        Flow::new(move |collector| {
-            let upstream = self.clone();
-            async move {
-                let (tx, mut rx) = mpsc::channel(capacity);
-
-                let producer = tokio::spawn(async move {
-                    upstream
-                        .collect(move |value| {
-                            let tx = tx.clone();
-                            async move {
-                                let _ = tx.send(value).await;
-                            }
-                        })
-                        .await;
-                });
-
-                while let Some(value) = rx.recv().await {
-                    collector.emit(value).await;
-                }
+        buffering::buffer(self, capacity)
+    }
 
-                let _ = producer.await;
-            }
-        })
+    fn buffer_with(
+        self,
+        capacity: usize,
+        overflow: BufferOverflow,
+        on_drop: Option<DropHook<T>>,
+    ) -> Flow<T> {
+        buffering::buffer_with(self, capacity, overflow, on_drop)
     }
 
     fn flow_on(self, dispatcher: Dispatcher) -> Flow<T> {
@@ -140,23 +148,14 @@ where
             let upstream = self.clone();
             let dispatcher = dispatcher.clone();
             async move {
-                let (tx, mut rx) = mpsc::channel(16);
+                let (tx, rx) = mpsc::channel(16);
 
-                let producer_dispatcher = dispatcher.clone();
-                producer_dispatcher.spawn(async move {
-                    upstream
-                        .collect(move |value| {
-                            let tx = tx.clone();
-                            async move {
-                                let _ = tx.send(value).await;
-                            }
-                        })
-                        .await;
+                let (producer, _guard) = AbortOnDrop::wrap(async move {
+                    upstream.send_to(tx).await;
                 });
+                dispatcher.spawn(producer);
 
-                while let Some(value) = rx.recv().await {
-                    collector.emit(value).await;
-                }
+                collector.emit_all(rx).await;
             }
         })
     }
@@ -174,32 +173,39 @@ where
             async move {
                 let (tx, mut rx) = mpsc::channel::<Flow<U>>(1);
 
-                let producer = tokio::spawn({
-                    let f = Arc::clone(&f);
-                    async move {
-                        upstream
-                            .collect(move |value| {
-                                let f = Arc::clone(&f);
-                                let tx = tx.clone();
-                                async move {
-                                    let flow = f(value).await;
-                                    let _ = tx.send(flow).await;
-                                }
-                            })
-                            .await;
-                    }
-                });
-
-                while let Some(inner_flow) = rx.recv().await {
-                    let collector = collector.clone();
-                    inner_flow
+                let (producer, _guard) = AbortOnDrop::wrap(async move {
+                    upstream
                         .collect(move |value| {
-                            let collector = collector.clone();
+                            let f = Arc::clone(&f);
+                            let tx = tx.clone();
                             async move {
-                                collector.emit(value).await;
+                                let flow = f(value).await;
+                                let _ = tx.send(flow).await;
                             }
                         })
                         .await;
+                });
+                let producer = tokio::spawn(producer);
+
+                let mut next = rx.recv().await;
+                while let Some(inner_flow) = next.take() {
+                    let collector = collector.clone();
+                    let inner = inner_flow.collect(move |value| {
+                        let collector = collector.clone();
+                        async move {
+                            collector.emit(value).await;
+                        }
+                    });
+                    tokio::pin!(inner);
+
+                    // A newer inner flow drops (aborts) the current collection
+                    tokio::select! {
+                        _ = &mut inner => next = rx.recv().await,
+                        newer = rx.recv() => match newer {
+                            Some(flow) => next = Some(flow),
+                            None => inner.await, // Upstream completed: finish the last one
+                        },
+                    }
                 }
 
                 let _ = producer.await;
@@ -268,6 +274,32 @@ where
         })
     }
 
+    fn switch_map<U, F, Fut>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Flow<U>> + Send + 'static,
+    {
+        self.flat_map_latest(f)
+    }
+
+    fn switch_map_sync<U, F>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Flow<U> + Send + Sync + 'static,
+    {
+        self.flat_map_latest_sync(f)
+    }
+
+    fn flat_map_concat<U, F, Fut>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Flow<U>> + Send + 'static,
+    {
+        self.flat_map(f)
+    }
+
     fn flat_map<U, F, Fut>(self, f: F) -> Flow<U>
     where
         U: Send + 'static,
@@ -361,6 +393,241 @@ where
         })
     }
 
+    fn on_each_on<F, Fut>(self, dispatcher: Dispatcher, f: F) -> Flow<T>
+    where
+        F: Fn(&T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+        T: Clone,
+    {
+        transform::on_each_on(self, dispatcher, f)
+    }
+
+    fn on_each_parallel<F, Fut>(self, concurrency: usize, f: F) -> Flow<T>
+    where
+        F: Fn(&T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+        T: Clone,
+    {
+        let concurrency = concurrency.max(1);
+        let f = Arc::new(f);
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let f = Arc::clone(&f);
+            async move {
+                let semaphore = Arc::new(Semaphore::new(concurrency));
+                // Side effects are queued in upstream order and awaited in that order.
+                // Each is aborted if the collection is dropped before it is awaited.
+                let (tx, mut rx) = mpsc::channel::<(T, JoinHandle<()>, AbortOnDrop)>(concurrency);
+
+                let (producer, _guard) = AbortOnDrop::wrap(async move {
+                    upstream
+                        .collect(move |value| {
+                            let f = Arc::clone(&f);
+                            let tx = tx.clone();
+                            let semaphore = Arc::clone(&semaphore);
+                            async move {
+                                let Ok(permit) = semaphore.acquire_owned().await else {
+                                    return;
+                                };
+                                let effect = f(&value);
+                                let (effect, guard) = AbortOnDrop::wrap(async move {
+                                    effect.await;
+                                    drop(permit);
+                                });
+                                let handle = tokio::spawn(effect);
+                                let _ = tx.send((value, handle, guard)).await;
+                            }
+                        })
+                        .await;
+                });
+                let producer = tokio::spawn(producer);
+
+                while let Some((value, handle, _effect_guard)) = rx.recv().await {
+                    let _ = handle.await;
+                    collector.emit(value).await;
+                }
+
+                let _ = producer.await;
+            }
+        })
+    }
+
+    fn on_first<F>(self, f: F) -> Flow<T>
+    where
+        F: Fn(&T) + Send + Sync + 'static,
+        T: Clone,
+    {
+        let f = Arc::new(f);
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let f = Arc::clone(&f);
+            async move {
+                let fired = Arc::new(AtomicBool::new(false));
+                upstream
+                    .collect(move |value| {
+                        let f = Arc::clone(&f);
+                        let collector = collector.clone();
+                        let fired = Arc::clone(&fired);
+                        async move {
+                            if !fired.swap(true, Ordering::SeqCst) {
+                                f(&value);
+                            }
+                            collector.emit(value).await;
+                        }
+                    })
+                    .await;
+            }
+        })
+    }
+
+    fn on_last<F>(self, f: F) -> Flow<T>
+    where
+        F: Fn(&T) + Send + Sync + 'static,
+        T: Clone,
+    {
+        let f = Arc::new(f);
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            let f = Arc::clone(&f);
+            async move {
+                let last = Arc::new(tokio::sync::Mutex::new(None::<T>));
+                let last_clone = Arc::clone(&last);
+                upstream
+                    .collect(move |value| {
+                        let collector = collector.clone();
+                        let last = Arc::clone(&last_clone);
+                        async move {
+                            *last.lock().await = Some(value.clone());
+                            collector.emit(value).await;
+                        }
+                    })
+                    .await;
+
+                let last_value = last.lock().await.take();
+                if let Some(value) = last_value {
+                    f(&value);
+                }
+            }
+        })
+    }
+
+    fn materialize(self) -> Flow<Notification<T>> {
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            async move {
+                let collector_clone = collector.clone();
+                upstream
+                    .collect(move |value| {
+                        let collector = collector_clone.clone();
+                        async move {
+                            collector.emit(Notification::Value(value)).await;
+                        }
+                    })
+                    .await;
+
+                collector.emit(Notification::Complete).await;
+            }
+        })
+    }
+
+    fn default_if_empty(self, value: T) -> Flow<T>
+    where
+        T: Clone + Sync,
+    {
+        self.on_empty(move |collector| async move {
+            collector.emit(value).await;
+        })
+    }
+
+    fn trace(self, label: &'static str) -> Flow<T>
+    where
+        T: std::fmt::Debug,
+    {
+        transform::trace(self, label)
+    }
+
+    fn chunked_by<K, F>(self, key: F) -> Flow<Vec<T>>
+    where
+        K: PartialEq + Send + 'static,
+        F: Fn(&T) -> K + Send + Sync + 'static,
+        T: Clone,
+    {
+        transform::chunked_by(self, key)
+    }
+
+    fn run_length(self) -> Flow<(T, usize)>
+    where
+        T: PartialEq,
+    {
+        transform::run_length(self)
+    }
+
+    fn cancel_on<F>(self, predicate: F) -> Flow<T>
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        transform::cancel_on(self, predicate)
+    }
+
+    fn take_until_notified(self, token: CancelToken) -> Flow<T> {
+        transform::take_until_notified(self, token)
+    }
+
+    fn with_previous(self) -> Flow<(Option<T>, T)>
+    where
+        T: Clone,
+    {
+        transform::with_previous(self)
+    }
+
+    fn assert_ordered(self) -> Flow<T>
+    where
+        T: PartialOrd + Clone,
+    {
+        transform::assert_ordered(self)
+    }
+
+    fn map_with_index<U, F>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(usize, T) -> U + Send + Sync + 'static,
+    {
+        transform::map_with_index(self, f)
+    }
+
+    fn on_backpressure_drop(self) -> (Flow<T>, DropCounter) {
+        buffering::on_backpressure_drop(self)
+    }
+
+    fn on_backpressure_latest(self) -> (Flow<T>, DropCounter) {
+        buffering::on_backpressure_latest(self)
+    }
+
+    fn share(self) -> Flow<T>
+    where
+        T: Clone,
+    {
+        sharing::share(self)
+    }
+
+    fn forward_to(self, scope: &CoroutineScope, target: SharedFlow<T>) -> JobHandle
+    where
+        T: Clone,
+    {
+        sharing::forward_to(self, scope, target)
+    }
+
+    fn publish(self) -> ConnectableFlow<T>
+    where
+        T: Clone,
+    {
+        ConnectableFlow::new(self)
+    }
+
+    fn yielding(self) -> Flow<T> {
+        transform::yielding(self)
+    }
+
     fn drop_first(self, count: usize) -> Flow<T> {
         Flow::new(move |collector| {
             let upstream = self.clone();
@@ -382,6 +649,46 @@ where
         })
     }
 
+    fn buffer_all(self) -> Flow<T> {
+        transform::buffer_all(self, |_| {})
+    }
+
+    fn sorted(self) -> Flow<T>
+    where
+        T: Ord,
+    {
+        transform::buffer_all(self, |values| values.sort())
+    }
+
+    fn sorted_by<F>(self, compare: F) -> Flow<T>
+    where
+        F: Fn(&T, &T) -> std::cmp::Ordering + Send + Sync + 'static,
+    {
+        transform::buffer_all(self, move |values| values.sort_by(&compare))
+    }
+
+    fn sorted_by_key<K, F>(self, key: F) -> Flow<T>
+    where
+        K: Ord,
+        F: Fn(&T) -> K + Send + Sync + 'static,
+    {
+        transform::buffer_all(self, move |values| values.sort_by_key(&key))
+    }
+
+    fn take_last(self, count: usize) -> Flow<T>
+    where
+        T: Clone,
+    {
+        transform::take_last(self, count)
+    }
+
+    fn drop_last(self, count: usize) -> Flow<T>
+    where
+        T: Clone,
+    {
+        transform::drop_last(self, count)
+    }
+
     fn drop_while<F>(self, predicate: F) -> Flow<T>
     where
         F: Fn(&T) -> bool + Send + Sync + 'static,
@@ -447,22 +754,50 @@ where
     where
         T: Clone + PartialEq,
     {
+        transform::distinct_until_changed_with(self, |prev, value| prev == value)
+    }
+
+    fn distinct_until_changed_with<F>(self, eq: F) -> Flow<T>
+    where
+        F: Fn(&T, &T) -> bool + Send + Sync + 'static,
+        T: Clone,
+    {
+        transform::distinct_until_changed_with(self, eq)
+    }
+
+    fn distinct_recent(self, window: usize) -> Flow<T>
+    where
+        T: Clone + Eq + Hash,
+    {
+        transform::distinct_recent(self, window)
+    }
+
+    fn distinct_until_changed_by<K, F>(self, key_selector: F) -> Flow<T>
+    where
+        K: PartialEq + Send + 'static,
+        F: Fn(&T) -> K + Send + Sync + 'static,
+        T: Clone,
+    {
+        let key_selector = Arc::new(key_selector);
         Flow::new(move |collector| {
             let upstream = self.clone();
+            let key_selector = Arc::clone(&key_selector);
             async move {
-                let last = Arc::new(tokio::sync::Mutex::new(None::<T>));
+                let last_key = Arc::new(tokio::sync::Mutex::new(None::<K>));
                 upstream
                     .collect(move |value| {
                         let collector = collector.clone();
-                        let last = Arc::clone(&last);
+                        let last_key = Arc::clone(&last_key);
+                        let key_selector = Arc::clone(&key_selector);
                         async move {
-                            let mut guard = last.lock().await;
+                            let key = key_selector(&value);
+                            let mut guard = last_key.lock().await;
                             let should_emit = match &*guard {
                                 None => true,
-                                Some(prev) => prev != &value,
+                                Some(prev_key) => prev_key != &key,
                             };
                             if should_emit {
-                                *guard = Some(value.clone());
+                                *guard = Some(key);
                                 drop(guard);
                                 collector.emit(value).await;
                             }
@@ -473,10 +808,11 @@ where
         })
     }
 
-    fn distinct_until_changed_by<K, F>(self, key_selector: F) -> Flow<T>
+    fn distinct_until_changed_by_async<K, F, Fut>(self, key_selector: F) -> Flow<T>
     where
         K: PartialEq + Send + 'static,
-        F: Fn(&T) -> K + Send + Sync + 'static,
+        F: Fn(&T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = K> + Send + 'static,
         T: Clone,
     {
         let key_selector = Arc::new(key_selector);
@@ -491,7 +827,7 @@ where
                         let last_key = Arc::clone(&last_key);
                         let key_selector = Arc::clone(&key_selector);
                         async move {
-                            let key = key_selector(&value);
+                            let key = key_selector(&value).await;
                             let mut guard = last_key.lock().await;
                             let should_emit = match &*guard {
                                 None => true,
@@ -508,4 +844,131 @@ where
             }
         })
     }
+
+    fn scan_async<R, F, Fut>(self, initial: R, f: F) -> Flow<R>
+    where
+        R: Clone + Send + Sync + 'static,
+        F: Fn(R, T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = R> + Send + 'static,
+    {
+        transform::scan_async(self, initial, f)
+    }
+
+    fn scan_complete<R, F, G, Fut>(self, initial: R, step: F, on_complete: G) -> Flow<R>
+    where
+        R: Clone + Send + Sync + 'static,
+        F: Fn(R, T) -> R + Send + Sync + 'static,
+        G: Fn(R, FlowCollector<R>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        transform::scan_complete(self, initial, step, on_complete)
+    }
+
+    fn chunked_timeout(self, max_size: usize, max_wait: Duration) -> Flow<Vec<T>> {
+        timing::chunked_timeout(self, max_size, max_wait)
+    }
+
+    fn throttle(self, period: Duration) -> Flow<T> {
+        timing::throttle(self, period)
+    }
+
+    fn throttle_leading_trailing(self, period: Duration) -> Flow<T> {
+        timing::throttle_leading_trailing(self, period)
+    }
+
+    fn timestamp(self) -> Flow<(Instant, T)> {
+        timing::timestamp(self)
+    }
+
+    fn time_interval(self) -> Flow<(Duration, T)> {
+        timing::time_interval(self)
+    }
+
+    fn distinct_window(self, window: Duration) -> Flow<T>
+    where
+        T: Clone + Eq + Hash,
+    {
+        timing::distinct_window(self, window)
+    }
+
+    fn debounce(self, timeout: Duration) -> Flow<T> {
+        timing::debounce(self, timeout)
+    }
+
+    fn keepalive(self, period: Duration) -> Flow<T>
+    where
+        T: Clone,
+    {
+        timing::keepalive(self, period)
+    }
+
+    fn debounce_on<O>(self, timeout: Duration, options: O) -> Flow<T>
+    where
+        O: Into<TimingOptions>,
+    {
+        options.into().pin(self.debounce(timeout))
+    }
+
+    fn throttle_on<O>(self, period: Duration, options: O) -> Flow<T>
+    where
+        O: Into<TimingOptions>,
+    {
+        options.into().pin(self.throttle(period))
+    }
+
+    fn heartbeat_sample(self, period: Duration) -> Flow<Option<T>> {
+        timing::heartbeat_sample(self, period)
+    }
+
+    fn keepalive_on<O>(self, period: Duration, options: O) -> Flow<T>
+    where
+        T: Clone,
+        O: Into<TimingOptions>,
+    {
+        options.into().pin(self.keepalive(period))
+    }
+
+    fn metrics_every<F>(self, period: Duration, on_tick: F) -> Flow<T>
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        timing::metrics_every(self, period, on_tick)
+    }
+}
+
+impl<T> Flow<Notification<T>>
+where
+    T: Send + 'static,
+{
+    /// Reverse `materialize`: unwrap `Notification::Value`s and stop at
+    /// the first `Notification::Complete`
+    pub fn dematerialize(self) -> Flow<T> {
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            async move {
+                let complete = Arc::new(Notify::new());
+                let complete_clone = Arc::clone(&complete);
+                let collection = upstream.collect(move |notification| {
+                    let collector = collector.clone();
+                    let complete = Arc::clone(&complete_clone);
+                    async move {
+                        match notification {
+                            Notification::Value(value) => collector.emit(value).await,
+                            Notification::Complete => {
+                                complete.notify_one();
+                                // Park the upstream until the select below drops it
+                                std::future::pending::<()>().await;
+                            }
+                        }
+                    }
+                });
+
+                tokio::select! {
+                    biased;
+                    _ = complete.notified() => {}
+                    _ = collection => {}
+                }
+            }
+        })
+    }
 }
@@ -1,8 +1,94 @@
-use crate::flow::Flow;
-use rs_coroutine_core::Dispatcher;
+use crate::flow::{Flow, FlowCollector};
+use crate::hot_flow::{ConnectableFlow, SharedFlow};
+use rs_coroutine_core::{CancelToken, CoroutineScope, Dispatcher, JobHandle};
 use std::future::Future;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A flow event reified as a value, produced by `FlowExt::materialize`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification<T> {
+    /// An emitted value
+    Value(T),
+    /// The upstream completed
+    Complete,
+}
+
+/// Number of values dropped by a backpressure operator, shared with the flow
+#[derive(Debug, Clone, Default)]
+pub struct DropCounter(Arc<AtomicUsize>);
+
+impl DropCounter {
+    /// Values dropped so far, across all collections of the flow
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn increment(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// What `FlowExt::buffer_with` does with a value arriving at a full buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BufferOverflow {
+    /// Suspend the upstream until the collector makes room
+    #[default]
+    Suspend,
+    /// Evict the oldest buffered value to make room for the new one
+    DropOldest,
+    /// Discard the new value and keep the buffer as it is
+    DropLatest,
+}
+
+/// Callback invoked with each value a buffer drops on overflow
+pub type DropHook<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+/// Where time-based operators run, used by `debounce_on`, `throttle_on`,
+/// `keepalive_on` and `interval_flow_on`
+///
+/// With a dispatcher the operator is wrapped in `flow_on`: the operator, its
+/// timers and everything upstream of it run on the dispatcher, while values
+/// are still emitted on the collecting context.
+#[derive(Clone, Default)]
+pub struct TimingOptions {
+    /// Dispatcher for the operator and its upstream; `None` keeps them on the
+    /// collecting context
+    pub dispatcher: Option<Dispatcher>,
+}
+
+impl TimingOptions {
+    /// Run the operator and its upstream on `dispatcher`
+    pub fn on(dispatcher: Dispatcher) -> Self {
+        Self {
+            dispatcher: Some(dispatcher),
+        }
+    }
+
+    /// Apply `flow_on` to `flow` if a dispatcher is set
+    pub(crate) fn pin<T: Send + 'static>(&self, flow: Flow<T>) -> Flow<T> {
+        match &self.dispatcher {
+            Some(dispatcher) => flow.flow_on(dispatcher.clone()),
+            None => flow,
+        }
+    }
+}
+
+impl From<Dispatcher> for TimingOptions {
+    fn from(dispatcher: Dispatcher) -> Self {
+        Self::on(dispatcher)
+    }
+}
 
 /// Extension methods for Flow
+///
+/// Operators such as `map` and `filter` run inline: each value passes through
+/// the chain as nested calls, which return before the next value is processed.
+/// Stack depth therefore grows with the number of chained operators, not with
+/// the number of values. Very long chains built at runtime can insert `buffer`
+/// to run the upstream part in its own task, which starts again from a shallow stack.
 pub trait FlowExt<T>: Sized
 where
     T: Send + 'static,
@@ -43,16 +129,110 @@ where
         F: Fn(&T) -> bool + Send + Sync + 'static,
         T: Clone;
 
+    /// Map each value and emit only the `Some` results (sync)
+    ///
+    /// # Example
+    /// ```ignore
+    /// strings.filter_map(|s| s.parse::<i32>().ok())
+    /// ```
+    fn filter_map<U, F>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Option<U> + Send + Sync + 'static;
+
+    /// Map each value asynchronously and emit only the `Some` results
+    ///
+    /// # Example
+    /// ```ignore
+    /// ids.filter_map_async(|id| async move { cache.lookup(id).await })
+    /// ```
+    fn filter_map_async<U, F, Fut>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<U>> + Send + 'static;
+
     /// Take only the first n values
     fn take(self, count: usize) -> Flow<T>;
 
-    /// Buffer emissions
+    /// Collect every upstream value before emitting any of them downstream.
+    /// Memory use grows with the length of the upstream.
+    fn buffer_all(self) -> Flow<T>;
+
+    /// Collect every upstream value, then emit them in sorted order.
+    /// Memory use grows with the length of the upstream.
+    ///
+    /// # Example
+    /// ```ignore
+    /// scores.sorted().take(10)
+    /// ```
+    fn sorted(self) -> Flow<T>
+    where
+        T: Ord;
+
+    /// Collect every upstream value, then emit them sorted with `compare`.
+    /// The sort is stable. Memory use grows with the length of the upstream.
+    ///
+    /// # Example
+    /// ```ignore
+    /// events.sorted_by(|a, b| b.priority.cmp(&a.priority))
+    /// ```
+    fn sorted_by<F>(self, compare: F) -> Flow<T>
+    where
+        F: Fn(&T, &T) -> std::cmp::Ordering + Send + Sync + 'static;
+
+    /// Collect every upstream value, then emit them sorted by `key`.
+    /// The sort is stable. Memory use grows with the length of the upstream.
+    ///
+    /// # Example
+    /// ```ignore
+    /// users.sorted_by_key(|user| user.age)
+    /// ```
+    fn sorted_by_key<K, F>(self, key: F) -> Flow<T>
+    where
+        K: Ord,
+        F: Fn(&T) -> K + Send + Sync + 'static;
+
+    /// Take only the last n values, emitted once the upstream completes
+    fn take_last(self, count: usize) -> Flow<T>
+    where
+        T: Clone;
+
+    /// Buffer emissions. The upstream is collected in its own task.
     fn buffer(self, capacity: usize) -> Flow<T>;
 
-    /// Switch to a different dispatcher for upstream collection
+    /// Buffer up to `capacity` emissions, handling a full buffer according to
+    /// `overflow`. Each value dropped on overflow is passed to `on_drop`, if set.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let dropped = Arc::new(AtomicUsize::new(0));
+    /// let counter = Arc::clone(&dropped);
+    /// let frames = camera_frames.buffer_with(
+    ///     8,
+    ///     BufferOverflow::DropOldest,
+    ///     Some(Arc::new(move |_| { counter.fetch_add(1, Ordering::SeqCst); })),
+    /// );
+    /// ```
+    fn buffer_with(
+        self,
+        capacity: usize,
+        overflow: BufferOverflow,
+        on_drop: Option<DropHook<T>>,
+    ) -> Flow<T>;
+
+    /// Switch to a different dispatcher for upstream collection.
+    ///
+    /// Only the upstream moves: it is collected in a task on `dispatcher`
+    /// and its values are handed over through a channel, so the downstream
+    /// collector keeps running on the caller's context. Emitting from other
+    /// tasks into a collector directly is not supported; use `flow_on` or
+    /// `channel_flow` for that.
     fn flow_on(self, dispatcher: Dispatcher) -> Flow<T>;
 
-    /// Flat map to the latest flow, cancelling previous (async)
+    /// Flat map to the latest flow, cancelling previous (async).
+    /// When a new upstream value arrives, the collection of the current inner
+    /// flow is aborted and the inner flow for the new value takes over.
     fn flat_map_latest<U, F, Fut>(self, f: F) -> Flow<U>
     where
         U: Send + 'static,
@@ -65,13 +245,47 @@ where
         U: Send + 'static,
         F: Fn(T) -> Flow<U> + Send + Sync + 'static;
 
-    /// Transform each value and flatten (async)
+    /// Alias of `flat_map_latest` under its Rx name (switchMap)
+    ///
+    /// # Example
+    /// ```ignore
+    /// queries.switch_map(|query| async move { search(query) })
+    /// ```
+    fn switch_map<U, F, Fut>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Flow<U>> + Send + 'static;
+
+    /// Alias of `flat_map_latest_sync` under its Rx name (switchMap)
+    fn switch_map_sync<U, F>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Flow<U> + Send + Sync + 'static;
+
+    /// Transform each value and flatten (async).
+    /// Inner flows are collected one after another, in upstream order.
     fn flat_map<U, F, Fut>(self, f: F) -> Flow<U>
     where
         U: Send + 'static,
         F: Fn(T) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = Flow<U>> + Send + 'static;
 
+    /// Transform each value and flatten, guaranteeing upstream order: each
+    /// inner flow is collected to completion before the next one starts.
+    /// Same as `flat_map`; use `flat_map_latest` to cancel the previous
+    /// inner flow instead, or `merge` to collect inner flows concurrently.
+    ///
+    /// # Example
+    /// ```ignore
+    /// pages.flat_map_concat(|page| async move { fetch_rows(page) })
+    /// ```
+    fn flat_map_concat<U, F, Fut>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Flow<U>> + Send + 'static;
+
     /// Transform each value and flatten (sync)
     fn flat_map_sync<U, F>(self, f: F) -> Flow<U>
     where
@@ -91,9 +305,227 @@ where
         Fut: Future<Output = ()> + Send + 'static,
         T: Clone;
 
+    /// Perform an async side effect for each value, running up to `concurrency`
+    /// effects at once while still emitting values in upstream order.
+    /// A value is emitted once its own side effect has finished.
+    fn on_each_parallel<F, Fut>(self, concurrency: usize, f: F) -> Flow<T>
+    where
+        F: Fn(&T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+        T: Clone;
+
+    /// Perform an async side effect for each value on `dispatcher`, while the
+    /// upstream and the emission stay on the collecting context.
+    /// A value is emitted once its side effect has finished.
+    ///
+    /// # Example
+    /// ```ignore
+    /// events.on_each_on(Dispatchers::io(), |event| audit_log.write(event.clone()))
+    /// ```
+    fn on_each_on<F, Fut>(self, dispatcher: Dispatcher, f: F) -> Flow<T>
+    where
+        F: Fn(&T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+        T: Clone;
+
+    /// Perform a side effect for the first emitted value only
+    fn on_first<F>(self, f: F) -> Flow<T>
+    where
+        F: Fn(&T) + Send + Sync + 'static,
+        T: Clone;
+
+    /// Perform a side effect for the last emitted value only.
+    /// The effect runs once the upstream completes, after the value was emitted.
+    fn on_last<F>(self, f: F) -> Flow<T>
+    where
+        F: Fn(&T) + Send + Sync + 'static,
+        T: Clone;
+
+    /// Wrap each value in `Notification::Value` and emit a final
+    /// `Notification::Complete` when the upstream completes
+    fn materialize(self) -> Flow<Notification<T>>;
+
+    /// Emit `value` if the upstream completes without emitting anything,
+    /// otherwise pass values through unchanged
+    ///
+    /// # Example
+    /// ```ignore
+    /// search_results.default_if_empty(NoResults)
+    /// ```
+    fn default_if_empty(self, value: T) -> Flow<T>
+    where
+        T: Clone + Sync;
+
+    /// Log each emission with the given label, passing values through unchanged.
+    ///
+    /// Values are logged at debug level under the `coroflow::trace` target
+    /// when the `log` feature is enabled; otherwise this is a no-op.
+    ///
+    /// # Example
+    /// ```ignore
+    /// flow.trace("after-filter")
+    /// ```
+    fn trace(self, label: &'static str) -> Flow<T>
+    where
+        T: std::fmt::Debug;
+
+    /// Group consecutive values with equal keys into runs, emitting each run
+    /// when the key changes and the last one when the upstream completes
+    ///
+    /// # Example
+    /// ```ignore
+    /// log_lines.chunked_by(|line| line.request_id)
+    /// ```
+    fn chunked_by<K, F>(self, key: F) -> Flow<Vec<T>>
+    where
+        K: PartialEq + Send + 'static,
+        F: Fn(&T) -> K + Send + Sync + 'static,
+        T: Clone;
+
+    /// Run-length encode the flow, emitting `(value, count)` for each run of
+    /// equal consecutive values
+    ///
+    /// # Example
+    /// ```ignore
+    /// pixels.run_length()
+    /// ```
+    fn run_length(self) -> Flow<(T, usize)>
+    where
+        T: PartialEq;
+
+    /// Complete the flow at the first value matching `predicate`, without
+    /// emitting it. The upstream stops being collected at that point.
+    ///
+    /// # Example
+    /// ```ignore
+    /// messages.cancel_on(|msg| msg.is_shutdown())
+    /// ```
+    fn cancel_on<F>(self, predicate: F) -> Flow<T>
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static;
+
+    /// Complete the flow once `token` is cancelled. The upstream stops being
+    /// collected at that point; an already cancelled token emits nothing.
+    ///
+    /// # Example
+    /// ```ignore
+    /// ticks.take_until_notified(job.cancel_token().clone())
+    /// ```
+    fn take_until_notified(self, token: CancelToken) -> Flow<T>;
+
+    /// Pair each value with the one emitted before it. The first value is
+    /// paired with `None`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// state.with_previous().map_sync(|(prev, curr)| diff(prev, curr))
+    /// ```
+    fn with_previous(self) -> Flow<(Option<T>, T)>
+    where
+        T: Clone;
+
+    /// Pass values through, panicking if a value is smaller than the one
+    /// before it. Intended for checking ordering invariants in tests.
+    ///
+    /// # Example
+    /// ```ignore
+    /// merged_timestamps.assert_ordered().to_vec().await
+    /// ```
+    fn assert_ordered(self) -> Flow<T>
+    where
+        T: PartialOrd + Clone;
+
+    /// Map each value together with its zero-based index, in a single
+    /// operator rather than indexing and mapping in separate layers.
+    ///
+    /// # Example
+    /// ```ignore
+    /// rows.map_with_index(|line, row| format!("{line}: {row}"))
+    /// ```
+    fn map_with_index<U, F>(self, f: F) -> Flow<U>
+    where
+        U: Send + 'static,
+        F: Fn(usize, T) -> U + Send + Sync + 'static;
+
+    /// Drop values that arrive while the collector is busy. At most one value
+    /// waits for the collector; the returned counter reports how many were dropped.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let (frames, dropped) = camera_frames.on_backpressure_drop();
+    /// ```
+    fn on_backpressure_drop(self) -> (Flow<T>, DropCounter);
+
+    /// Keep only the newest value while the collector is busy, replacing any
+    /// value still waiting; the returned counter reports how many were replaced.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let (prices, dropped) = ticker.on_backpressure_latest();
+    /// ```
+    fn on_backpressure_latest(self) -> (Flow<T>, DropCounter);
+
+    /// Multicast this flow to all concurrent collectors without a scope.
+    ///
+    /// The upstream is started by the first collector and shared by every
+    /// collector that joins while it runs; collectors joining late miss the
+    /// values emitted before they subscribed. The upstream is stopped when
+    /// the last collector goes away, and restarted by the next one.
+    ///
+    /// Every collector receives every value emitted while it is subscribed:
+    /// the upstream runs at most 64 values ahead of the slowest collector.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let prices = fetch_prices().share();
+    /// ```
+    fn share(self) -> Flow<T>
+    where
+        T: Clone;
+
+    /// Launch a coroutine in `scope` that collects this flow and emits each
+    /// value into `target`. Emission is fire-and-forget like
+    /// `SharedFlow::emit`; cancel the returned job to stop forwarding.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let job = sensor_readings().forward_to(&scope, readings.clone());
+    /// ```
+    fn forward_to(self, scope: &CoroutineScope, target: SharedFlow<T>) -> JobHandle
+    where
+        T: Clone;
+
+    /// Turn this flow into a `ConnectableFlow` whose upstream starts only
+    /// when `connect()` is called, after all subscribers have attached.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let published = prices.publish();
+    /// let a = published.as_flow();
+    /// let b = published.as_flow();
+    /// published.connect();
+    /// ```
+    fn publish(self) -> ConnectableFlow<T>
+    where
+        T: Clone;
+
+    /// Yield to the executor after each emission, letting other tasks make
+    /// progress while a busy pipeline is being collected.
+    ///
+    /// # Example
+    /// ```ignore
+    /// flow_range(0, 1_000_000).yielding()
+    /// ```
+    fn yielding(self) -> Flow<T>;
+
     /// Skip the first n values (Kotlin's drop)
     fn drop_first(self, count: usize) -> Flow<T>;
 
+    /// Skip the last n values, holding back the newest n until more arrive
+    fn drop_last(self, count: usize) -> Flow<T>
+    where
+        T: Clone;
+
     /// Skip values while predicate is true (sync)
     fn drop_while<F>(self, predicate: F) -> Flow<T>
     where
@@ -111,12 +543,208 @@ where
     where
         T: Clone + PartialEq;
 
+    /// Only emit distinct consecutive values, using `eq` to decide whether a
+    /// value equals the last emitted one
+    ///
+    /// # Example
+    /// ```ignore
+    /// readings.distinct_until_changed_with(|a, b| (a - b).abs() < 0.01)
+    /// ```
+    fn distinct_until_changed_with<F>(self, eq: F) -> Flow<T>
+    where
+        F: Fn(&T, &T) -> bool + Send + Sync + 'static,
+        T: Clone;
+
+    /// Suppress a value if it equals one of the last `window` emitted values
+    ///
+    /// # Example
+    /// ```ignore
+    /// tracks.distinct_recent(20) // no repeats within the last 20 songs
+    /// ```
+    fn distinct_recent(self, window: usize) -> Flow<T>
+    where
+        T: Clone + Eq + Hash;
+
     /// Only emit distinct consecutive values by key
     fn distinct_until_changed_by<K, F>(self, key_selector: F) -> Flow<T>
     where
         K: PartialEq + Send + 'static,
         F: Fn(&T) -> K + Send + Sync + 'static,
         T: Clone;
+
+    /// Only emit distinct consecutive values by a key computed asynchronously
+    ///
+    /// # Example
+    /// ```ignore
+    /// files.distinct_until_changed_by_async(|file| hash_file(file.clone()))
+    /// ```
+    fn distinct_until_changed_by_async<K, F, Fut>(self, key_selector: F) -> Flow<T>
+    where
+        K: PartialEq + Send + 'static,
+        F: Fn(&T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = K> + Send + 'static,
+        T: Clone;
+
+    /// Accumulate values with an async accumulator, emitting `initial` first
+    /// and then each intermediate accumulator
+    ///
+    /// # Example
+    /// ```ignore
+    /// flow.scan_async(0, |acc, x| async move { store(acc + x).await })
+    /// ```
+    fn scan_async<R, F, Fut>(self, initial: R, f: F) -> Flow<R>
+    where
+        R: Clone + Send + Sync + 'static,
+        F: Fn(R, T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = R> + Send + 'static;
+
+    /// Accumulate values like `scan_async` with a sync `step`, then call
+    /// `on_complete` with the final accumulator once the upstream completes,
+    /// so it can emit a summary.
+    ///
+    /// # Example
+    /// ```ignore
+    /// totals.scan_complete(0, |acc, x| acc + x, |total, collector| async move {
+    ///     collector.emit(total * 100).await;
+    /// })
+    /// ```
+    fn scan_complete<R, F, G, Fut>(self, initial: R, step: F, on_complete: G) -> Flow<R>
+    where
+        R: Clone + Send + Sync + 'static,
+        F: Fn(R, T) -> R + Send + Sync + 'static,
+        G: Fn(R, FlowCollector<R>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static;
+
+    /// Collect values into batches, emitting a batch when it reaches `max_size`
+    /// or when `max_wait` has elapsed since the batch started, whichever comes first.
+    /// A partial batch is flushed when the upstream completes.
+    ///
+    /// # Panics
+    /// Panics if `max_size` is zero.
+    ///
+    /// # Example
+    /// ```ignore
+    /// flow.chunked_timeout(100, Duration::from_millis(50))
+    /// ```
+    fn chunked_timeout(self, max_size: usize, max_wait: Duration) -> Flow<Vec<T>>;
+
+    /// Leading-edge throttling: the first value passes immediately and opens a
+    /// window of `period`; values arriving while the window is open are dropped.
+    ///
+    /// # Example
+    /// ```ignore
+    /// clicks.throttle(Duration::from_millis(300))
+    /// ```
+    fn throttle(self, period: Duration) -> Flow<T>;
+
+    /// Throttling with both edges: the first value passes immediately and
+    /// opens a window of `period`; the latest value seen while the window is
+    /// open is emitted when it closes, which opens the next window.
+    ///
+    /// # Example
+    /// ```ignore
+    /// scroll_positions.throttle_leading_trailing(Duration::from_millis(100))
+    /// ```
+    fn throttle_leading_trailing(self, period: Duration) -> Flow<T>;
+
+    /// Pair each value with the instant it was emitted
+    ///
+    /// # Example
+    /// ```ignore
+    /// requests.timestamp().map_sync(|(at, req)| (at.elapsed(), req))
+    /// ```
+    fn timestamp(self) -> Flow<(Instant, T)>;
+
+    /// Pair each value with the time elapsed since the previous emission
+    /// (or since collection started, for the first value)
+    ///
+    /// # Example
+    /// ```ignore
+    /// ticks.time_interval().map_sync(|(gap, _)| gap)
+    /// ```
+    fn time_interval(self) -> Flow<(Duration, T)>;
+
+    /// Suppress values already seen within the last `window`; a value is let
+    /// through again once `window` has passed since it was last emitted.
+    ///
+    /// # Example
+    /// ```ignore
+    /// alerts.distinct_window(Duration::from_secs(60))
+    /// ```
+    fn distinct_window(self, window: Duration) -> Flow<T>
+    where
+        T: Clone + Eq + Hash;
+
+    /// Emit a value only after `timeout` has passed without a newer value.
+    /// The pending value is flushed when the upstream completes.
+    ///
+    /// # Example
+    /// ```ignore
+    /// search_queries.debounce(Duration::from_millis(300))
+    /// ```
+    fn debounce(self, timeout: Duration) -> Flow<T>;
+
+    /// Re-emit the most recent value every `period` until a new value
+    /// arrives. Completes when the upstream completes.
+    ///
+    /// # Example
+    /// ```ignore
+    /// presence.keepalive(Duration::from_secs(30))
+    /// ```
+    fn keepalive(self, period: Duration) -> Flow<T>
+    where
+        T: Clone;
+
+    /// `debounce` followed by `flow_on` with the dispatcher in `options`, so
+    /// the debounce and everything upstream of it run there. Values are still
+    /// emitted on the collecting context.
+    ///
+    /// # Example
+    /// ```ignore
+    /// queries.debounce_on(Duration::from_millis(300), timer_dispatcher.clone())
+    /// ```
+    fn debounce_on<O>(self, timeout: Duration, options: O) -> Flow<T>
+    where
+        O: Into<TimingOptions>;
+
+    /// `throttle` followed by `flow_on` with the dispatcher in `options`, as in
+    /// `debounce_on`
+    fn throttle_on<O>(self, period: Duration, options: O) -> Flow<T>
+    where
+        O: Into<TimingOptions>;
+
+    /// Emit every `period`: `Some` with the latest value if one arrived since
+    /// the previous tick, `None` otherwise. Completes when the upstream completes.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let telemetry = readings.heartbeat_sample(Duration::from_secs(1));
+    /// ```
+    fn heartbeat_sample(self, period: Duration) -> Flow<Option<T>>;
+
+    /// `keepalive` followed by `flow_on` with the dispatcher in `options`, as
+    /// in `debounce_on`
+    fn keepalive_on<O>(self, period: Duration, options: O) -> Flow<T>
+    where
+        T: Clone,
+        O: Into<TimingOptions>;
+
+    /// Count emissions and call `on_tick` with the number of values seen in
+    /// each `period`, passing the values through unchanged. A final partial
+    /// window is reported when the upstream completes.
+    ///
+    /// # Example
+    /// ```ignore
+    /// requests.metrics_every(Duration::from_secs(1), |n| gauge.set(n))
+    /// ```
+    fn metrics_every<F>(self, period: Duration, on_tick: F) -> Flow<T>
+    where
+        F: Fn(usize) + Send + Sync + 'static;
 }
 
+mod buffering;
 mod implementation;
+mod numeric;
+mod sharing;
+mod timing;
+mod transform;
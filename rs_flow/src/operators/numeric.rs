@@ -0,0 +1,56 @@
+//! Operators for flows of numbers
+
+use crate::flow::Flow;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+impl Flow<f64> {
+    /// Emit the mean of the last `window` values for each incoming value.
+    ///
+    /// Until `window` values have arrived, the mean of the values seen so far
+    /// is emitted if `emit_partial` is set; otherwise nothing is emitted.
+    ///
+    /// # Panics
+    /// Panics if `window` is zero.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let smoothed = readings.moving_average(5, false);
+    /// ```
+    pub fn moving_average(self, window: usize, emit_partial: bool) -> Flow<f64> {
+        assert!(
+            window > 0,
+            "moving_average: window must be greater than zero"
+        );
+
+        Flow::new(move |collector| {
+            let upstream = self.clone();
+            async move {
+                // Values in the window and their running sum
+                let state = Arc::new(Mutex::new((VecDeque::with_capacity(window), 0.0)));
+                upstream
+                    .collect(move |value| {
+                        let collector = collector.clone();
+                        let state = Arc::clone(&state);
+                        async move {
+                            let average = {
+                                let mut guard = state.lock().unwrap();
+                                let (values, sum) = &mut *guard;
+                                values.push_back(value);
+                                *sum += value;
+                                if values.len() > window {
+                                    *sum -= values.pop_front().unwrap_or_default();
+                                }
+                                (values.len() == window || emit_partial)
+                                    .then(|| *sum / values.len() as f64)
+                            };
+                            if let Some(average) = average {
+                                collector.emit(average).await;
+                            }
+                        }
+                    })
+                    .await;
+            }
+        })
+    }
+}
@@ -0,0 +1,126 @@
+//! Multicasting operators
+//!
+//! The `FlowExt` implementation delegates these operators here.
+
+use crate::flow::Flow;
+use crate::hot_flow::SharedFlow;
+use crate::publisher::Publisher;
+use crate::task_guard::AbortOnDrop;
+use rs_coroutine_core::{CoroutineScope, JobHandle};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// How far the upstream may run ahead of the slowest shared collector
+const SHARE_BUFFER: usize = 64;
+
+/// Upstream run shared by the active collectors of a `share`d flow
+struct ShareState<T> {
+    subscribers: usize,
+    /// `None` marks upstream completion
+    tx: Option<Publisher<Option<T>>>,
+    producer: Option<AbortOnDrop>,
+}
+
+/// Releases a collector's subscription, stopping the upstream when the
+/// last collector goes away (including when its future is dropped)
+struct Subscription<T> {
+    state: Arc<Mutex<ShareState<T>>>,
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.subscribers -= 1;
+        if state.subscribers == 0 {
+            state.tx = None;
+            state.producer = None;
+        }
+    }
+}
+
+pub(super) fn share<T>(upstream: Flow<T>) -> Flow<T>
+where
+    T: Clone + Send + 'static,
+{
+    let state = Arc::new(Mutex::new(ShareState {
+        subscribers: 0,
+        tx: None,
+        producer: None,
+    }));
+
+    Flow::new(move |collector| {
+        let subscription = Subscription {
+            state: Arc::clone(&state),
+        };
+        let mut rx = {
+            let mut guard = state.lock().unwrap();
+            guard.subscribers += 1;
+            match &guard.tx {
+                Some(tx) => tx.subscribe(),
+                None => {
+                    // First collector: start the upstream
+                    let tx = Publisher::new(SHARE_BUFFER);
+                    let rx = tx.subscribe();
+                    let upstream = upstream.clone();
+                    let state = Arc::clone(&state);
+                    let producer_tx = tx.clone();
+                    let (producer, producer_guard) = AbortOnDrop::wrap(async move {
+                        upstream
+                            .collect(move |value| {
+                                let tx = producer_tx.clone();
+                                async move {
+                                    // Waits for the slowest collector
+                                    tx.send(Some(value)).await;
+                                }
+                            })
+                            .await;
+
+                        // Detach this run so later collectors start a fresh one
+                        let (tx, _producer) = {
+                            let mut guard = state.lock().unwrap();
+                            (guard.tx.take(), guard.producer.take())
+                        };
+                        if let Some(tx) = tx {
+                            tx.send(None).await;
+                        }
+                    });
+                    guard.tx = Some(tx);
+                    guard.producer = Some(producer_guard);
+                    tokio::spawn(producer);
+                    rx
+                }
+            }
+        };
+
+        async move {
+            let _subscription = subscription;
+            loop {
+                match rx.recv().await {
+                    Ok(Some(value)) => collector.emit(value).await,
+                    Ok(None) | Err(broadcast::error::RecvError::Closed) => break,
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        unreachable!("shared upstream waits for every collector")
+                    }
+                }
+            }
+        }
+    })
+}
+
+pub(super) fn forward_to<T>(
+    upstream: Flow<T>,
+    scope: &CoroutineScope,
+    target: SharedFlow<T>,
+) -> JobHandle
+where
+    T: Clone + Send + 'static,
+{
+    scope.launch(async move {
+        upstream
+            .collect(move |value| {
+                target.emit(value);
+                async {}
+            })
+            .await;
+    })
+}
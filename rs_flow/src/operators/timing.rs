@@ -0,0 +1,453 @@
+//! Time-based operators
+//!
+//! The `FlowExt` implementation delegates its timing operators here.
+
+use crate::flow::Flow;
+use crate::task_guard::AbortOnDrop;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::Instant;
+
+pub(super) fn chunked_timeout<T>(
+    upstream: Flow<T>,
+    max_size: usize,
+    max_wait: Duration,
+) -> Flow<Vec<T>>
+where
+    T: Send + 'static,
+{
+    assert!(
+        max_size > 0,
+        "chunked_timeout: max_size must be greater than zero"
+    );
+
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let (tx, mut rx) = mpsc::channel::<T>(16);
+
+            let (producer, _guard) = AbortOnDrop::wrap(async move {
+                upstream
+                    .collect(move |value| {
+                        let tx = tx.clone();
+                        async move {
+                            let _ = tx.send(value).await;
+                        }
+                    })
+                    .await;
+            });
+            let producer = tokio::spawn(producer);
+
+            let mut batch = Vec::with_capacity(max_size);
+            let mut deadline: Option<Instant> = None;
+
+            loop {
+                // `None` means the batch deadline elapsed before a value arrived
+                let next = match deadline {
+                    Some(deadline) => tokio::select! {
+                        value = rx.recv() => Some(value),
+                        _ = tokio::time::sleep_until(deadline) => None,
+                    },
+                    None => Some(rx.recv().await),
+                };
+
+                match next {
+                    Some(Some(value)) => {
+                        if batch.is_empty() {
+                            deadline = Some(Instant::now() + max_wait);
+                        }
+                        batch.push(value);
+                        if batch.len() >= max_size {
+                            deadline = None;
+                            let full = std::mem::replace(&mut batch, Vec::with_capacity(max_size));
+                            collector.emit(full).await;
+                        }
+                    }
+                    Some(None) => break, // Upstream completed
+                    None => {
+                        deadline = None;
+                        let partial = std::mem::replace(&mut batch, Vec::with_capacity(max_size));
+                        collector.emit(partial).await;
+                    }
+                }
+            }
+
+            if !batch.is_empty() {
+                collector.emit(batch).await;
+            }
+
+            let _ = producer.await;
+        }
+    })
+}
+
+pub(super) fn throttle<T>(upstream: Flow<T>, period: Duration) -> Flow<T>
+where
+    T: Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let window_start = Arc::new(Mutex::new(None::<Instant>));
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    let window_start = Arc::clone(&window_start);
+                    async move {
+                        let now = Instant::now();
+                        let mut guard = window_start.lock().await;
+                        let window_open = match *guard {
+                            Some(start) => now.duration_since(start) < period,
+                            None => false,
+                        };
+                        if !window_open {
+                            *guard = Some(now);
+                            drop(guard);
+                            collector.emit(value).await;
+                        }
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn throttle_leading_trailing<T>(upstream: Flow<T>, period: Duration) -> Flow<T>
+where
+    T: Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let (tx, mut rx) = mpsc::channel::<T>(16);
+
+            let (producer, _guard) = AbortOnDrop::wrap(async move {
+                upstream
+                    .collect(move |value| {
+                        let tx = tx.clone();
+                        async move {
+                            let _ = tx.send(value).await;
+                        }
+                    })
+                    .await;
+            });
+            let producer = tokio::spawn(producer);
+
+            let mut window_end: Option<Instant> = None;
+            let mut trailing: Option<T> = None;
+
+            loop {
+                // `None` means the window closed before a value arrived
+                let next = match window_end {
+                    Some(end) => tokio::select! {
+                        value = rx.recv() => Some(value),
+                        _ = tokio::time::sleep_until(end) => None,
+                    },
+                    None => Some(rx.recv().await),
+                };
+
+                match next {
+                    Some(Some(value)) => {
+                        if window_end.is_some() {
+                            trailing = Some(value);
+                        } else {
+                            window_end = Some(Instant::now() + period);
+                            collector.emit(value).await;
+                        }
+                    }
+                    Some(None) => break, // Upstream completed
+                    None => match trailing.take() {
+                        Some(value) => {
+                            window_end = Some(Instant::now() + period);
+                            collector.emit(value).await;
+                        }
+                        None => window_end = None,
+                    },
+                }
+            }
+
+            if let Some(value) = trailing {
+                collector.emit(value).await;
+            }
+
+            let _ = producer.await;
+        }
+    })
+}
+
+pub(super) fn debounce<T>(upstream: Flow<T>, timeout: Duration) -> Flow<T>
+where
+    T: Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let (tx, mut rx) = mpsc::channel::<T>(16);
+
+            let (producer, _guard) = AbortOnDrop::wrap(async move {
+                upstream
+                    .collect(move |value| {
+                        let tx = tx.clone();
+                        async move {
+                            let _ = tx.send(value).await;
+                        }
+                    })
+                    .await;
+            });
+            let producer = tokio::spawn(producer);
+
+            let mut pending: Option<T> = None;
+            let mut deadline = Instant::now();
+
+            loop {
+                // `None` means the quiet period elapsed before a newer value arrived
+                let next = if pending.is_some() {
+                    tokio::select! {
+                        value = rx.recv() => Some(value),
+                        _ = tokio::time::sleep_until(deadline) => None,
+                    }
+                } else {
+                    Some(rx.recv().await)
+                };
+
+                match next {
+                    Some(Some(value)) => {
+                        pending = Some(value);
+                        deadline = Instant::now() + timeout;
+                    }
+                    Some(None) => break, // Upstream completed
+                    None => {
+                        if let Some(value) = pending.take() {
+                            collector.emit(value).await;
+                        }
+                    }
+                }
+            }
+
+            if let Some(value) = pending {
+                collector.emit(value).await;
+            }
+
+            let _ = producer.await;
+        }
+    })
+}
+
+pub(super) fn keepalive<T>(upstream: Flow<T>, period: Duration) -> Flow<T>
+where
+    T: Clone + Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let (tx, mut rx) = mpsc::channel::<T>(16);
+
+            let (producer, _guard) = AbortOnDrop::wrap(async move {
+                upstream
+                    .collect(move |value| {
+                        let tx = tx.clone();
+                        async move {
+                            let _ = tx.send(value).await;
+                        }
+                    })
+                    .await;
+            });
+            let producer = tokio::spawn(producer);
+
+            let mut latest: Option<T> = None;
+            let mut deadline = Instant::now();
+
+            loop {
+                // `None` means `period` elapsed without a new value
+                let next = if latest.is_some() {
+                    tokio::select! {
+                        value = rx.recv() => Some(value),
+                        _ = tokio::time::sleep_until(deadline) => None,
+                    }
+                } else {
+                    Some(rx.recv().await)
+                };
+
+                match next {
+                    Some(Some(value)) => {
+                        latest = Some(value.clone());
+                        collector.emit(value).await;
+                    }
+                    Some(None) => break, // Upstream completed
+                    None => {
+                        if let Some(value) = latest.clone() {
+                            collector.emit(value).await;
+                        }
+                    }
+                }
+                deadline = Instant::now() + period;
+            }
+
+            let _ = producer.await;
+        }
+    })
+}
+
+pub(super) fn heartbeat_sample<T>(upstream: Flow<T>, period: Duration) -> Flow<Option<T>>
+where
+    T: Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let (tx, mut rx) = mpsc::channel::<T>(16);
+
+            let (producer, _guard) = AbortOnDrop::wrap(async move {
+                upstream
+                    .collect(move |value| {
+                        let tx = tx.clone();
+                        async move {
+                            let _ = tx.send(value).await;
+                        }
+                    })
+                    .await;
+            });
+            let producer = tokio::spawn(producer);
+
+            // Newest value received since the previous tick
+            let mut latest: Option<T> = None;
+            let mut ticks = tokio::time::interval_at(Instant::now() + period, period);
+
+            loop {
+                tokio::select! {
+                    value = rx.recv() => match value {
+                        Some(value) => latest = Some(value),
+                        None => break, // Upstream completed
+                    },
+                    _ = ticks.tick() => collector.emit(latest.take()).await,
+                }
+            }
+
+            let _ = producer.await;
+        }
+    })
+}
+
+pub(super) fn metrics_every<T, F>(upstream: Flow<T>, period: Duration, on_tick: F) -> Flow<T>
+where
+    T: Send + 'static,
+    F: Fn(usize) + Send + Sync + 'static,
+{
+    let on_tick = Arc::new(on_tick);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let on_tick = Arc::clone(&on_tick);
+        async move {
+            let count = Arc::new(AtomicUsize::new(0));
+
+            let (ticker, guard) = AbortOnDrop::wrap({
+                let count = Arc::clone(&count);
+                let on_tick = Arc::clone(&on_tick);
+                async move {
+                    let mut ticks = tokio::time::interval_at(Instant::now() + period, period);
+                    loop {
+                        ticks.tick().await;
+                        on_tick(count.swap(0, Ordering::SeqCst));
+                    }
+                }
+            });
+            tokio::spawn(ticker);
+
+            let counted = Arc::clone(&count);
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    counted.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        collector.emit(value).await;
+                    }
+                })
+                .await;
+
+            // Stop ticking before reporting the final partial window
+            drop(guard);
+            let remaining = count.swap(0, Ordering::SeqCst);
+            if remaining > 0 {
+                on_tick(remaining);
+            }
+        }
+    })
+}
+
+pub(super) fn timestamp<T>(upstream: Flow<T>) -> Flow<(std::time::Instant, T)>
+where
+    T: Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    async move {
+                        collector.emit((std::time::Instant::now(), value)).await;
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn time_interval<T>(upstream: Flow<T>) -> Flow<(Duration, T)>
+where
+    T: Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let previous = Arc::new(Mutex::new(Instant::now()));
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    let previous = Arc::clone(&previous);
+                    async move {
+                        let now = Instant::now();
+                        let elapsed = {
+                            let mut previous = previous.lock().await;
+                            now.duration_since(std::mem::replace(&mut *previous, now))
+                        };
+                        collector.emit((elapsed, value)).await;
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn distinct_window<T>(upstream: Flow<T>, window: Duration) -> Flow<T>
+where
+    T: Clone + Eq + Hash + Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let last_seen: Arc<Mutex<HashMap<T, Instant>>> = Arc::new(Mutex::new(HashMap::new()));
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    let last_seen = Arc::clone(&last_seen);
+                    async move {
+                        let now = Instant::now();
+                        let mut seen = last_seen.lock().await;
+                        seen.retain(|_, at| now.duration_since(*at) < window);
+                        if seen.contains_key(&value) {
+                            return;
+                        }
+                        seen.insert(value.clone(), now);
+                        drop(seen);
+                        collector.emit(value).await;
+                    }
+                })
+                .await;
+        }
+    })
+}
@@ -0,0 +1,615 @@
+//! Value-transforming operators
+//!
+//! The `FlowExt` implementation delegates these operators here.
+
+use crate::flow::{Flow, FlowCollector};
+use crate::task_guard::AbortOnDrop;
+use rs_coroutine_core::{CancelToken, Dispatcher};
+use std::collections::{HashSet, VecDeque};
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{oneshot, Mutex, Notify};
+
+pub(super) fn scan_async<T, R, F, Fut>(upstream: Flow<T>, initial: R, f: F) -> Flow<R>
+where
+    T: Send + 'static,
+    R: Clone + Send + Sync + 'static,
+    F: Fn(R, T) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = R> + Send + 'static,
+{
+    let f = Arc::new(f);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let f = Arc::clone(&f);
+        let initial = initial.clone();
+        async move {
+            collector.emit(initial.clone()).await;
+
+            let acc = Arc::new(Mutex::new(initial));
+            upstream
+                .collect(move |value| {
+                    let f = Arc::clone(&f);
+                    let collector = collector.clone();
+                    let acc = Arc::clone(&acc);
+                    async move {
+                        let mut guard = acc.lock().await;
+                        let next = f(guard.clone(), value).await;
+                        *guard = next.clone();
+                        drop(guard);
+                        collector.emit(next).await;
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn scan_complete<T, R, F, G, Fut>(
+    upstream: Flow<T>,
+    initial: R,
+    step: F,
+    on_complete: G,
+) -> Flow<R>
+where
+    T: Send + 'static,
+    R: Clone + Send + Sync + 'static,
+    F: Fn(R, T) -> R + Send + Sync + 'static,
+    G: Fn(R, FlowCollector<R>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let step = Arc::new(step);
+    let on_complete = Arc::new(on_complete);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let step = Arc::clone(&step);
+        let on_complete = Arc::clone(&on_complete);
+        let initial = initial.clone();
+        async move {
+            collector.emit(initial.clone()).await;
+
+            let acc = Arc::new(Mutex::new(initial));
+            let acc_clone = Arc::clone(&acc);
+            let collector_clone = collector.clone();
+            upstream
+                .collect(move |value| {
+                    let step = Arc::clone(&step);
+                    let collector = collector_clone.clone();
+                    let acc = Arc::clone(&acc_clone);
+                    async move {
+                        let next = {
+                            let mut guard = acc.lock().await;
+                            *guard = step(guard.clone(), value);
+                            guard.clone()
+                        };
+                        collector.emit(next).await;
+                    }
+                })
+                .await;
+
+            let last = acc.lock().await.clone();
+            on_complete(last, collector).await;
+        }
+    })
+}
+
+pub(super) fn filter_map<T, U, F>(upstream: Flow<T>, f: F) -> Flow<U>
+where
+    T: Send + 'static,
+    U: Send + 'static,
+    F: Fn(T) -> Option<U> + Send + Sync + 'static,
+{
+    let f = Arc::new(f);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let f = Arc::clone(&f);
+        async move {
+            upstream
+                .collect(move |value| {
+                    let f = Arc::clone(&f);
+                    let collector = collector.clone();
+                    async move {
+                        if let Some(mapped) = f(value) {
+                            collector.emit(mapped).await;
+                        }
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn filter_map_async<T, U, F, Fut>(upstream: Flow<T>, f: F) -> Flow<U>
+where
+    T: Send + 'static,
+    U: Send + 'static,
+    F: Fn(T) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<U>> + Send + 'static,
+{
+    let f = Arc::new(f);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let f = Arc::clone(&f);
+        async move {
+            upstream
+                .collect(move |value| {
+                    let f = Arc::clone(&f);
+                    let collector = collector.clone();
+                    async move {
+                        if let Some(mapped) = f(value).await {
+                            collector.emit(mapped).await;
+                        }
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn trace<T>(upstream: Flow<T>, label: &'static str) -> Flow<T>
+where
+    T: std::fmt::Debug + Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    async move {
+                        #[cfg(feature = "log")]
+                        log::debug!(target: "coroflow::trace", "[{}] {:?}", label, value);
+                        #[cfg(not(feature = "log"))]
+                        let _ = label;
+                        collector.emit(value).await;
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn yielding<T>(upstream: Flow<T>) -> Flow<T>
+where
+    T: Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    async move {
+                        collector.emit(value).await;
+                        rs_coroutine_core::yield_now().await;
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn with_previous<T>(upstream: Flow<T>) -> Flow<(Option<T>, T)>
+where
+    T: Clone + Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let previous = Arc::new(Mutex::new(None::<T>));
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    let previous = Arc::clone(&previous);
+                    async move {
+                        let prior = previous.lock().await.replace(value.clone());
+                        collector.emit((prior, value)).await;
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn assert_ordered<T>(upstream: Flow<T>) -> Flow<T>
+where
+    T: PartialOrd + Clone + Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let previous = Arc::new(Mutex::new(None::<T>));
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    let previous = Arc::clone(&previous);
+                    async move {
+                        let prior = previous.lock().await.replace(value.clone());
+                        if prior.is_some_and(|prior| value < prior) {
+                            panic!("assert_ordered: value emitted out of order");
+                        }
+                        collector.emit(value).await;
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn distinct_recent<T>(upstream: Flow<T>, window: usize) -> Flow<T>
+where
+    T: Clone + Eq + Hash + Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            // The last `window` emitted values, in order and for lookup
+            let recent = Arc::new(Mutex::new((VecDeque::<T>::new(), HashSet::<T>::new())));
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    let recent = Arc::clone(&recent);
+                    async move {
+                        if window > 0 {
+                            let mut guard = recent.lock().await;
+                            let (order, seen) = &mut *guard;
+                            if seen.contains(&value) {
+                                return;
+                            }
+                            if order.len() == window {
+                                if let Some(oldest) = order.pop_front() {
+                                    seen.remove(&oldest);
+                                }
+                            }
+                            order.push_back(value.clone());
+                            seen.insert(value.clone());
+                        }
+                        collector.emit(value).await;
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn distinct_until_changed_with<T, F>(upstream: Flow<T>, eq: F) -> Flow<T>
+where
+    T: Clone + Send + 'static,
+    F: Fn(&T, &T) -> bool + Send + Sync + 'static,
+{
+    let eq = Arc::new(eq);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let eq = Arc::clone(&eq);
+        async move {
+            // Compared against the last emitted value
+            let last = Arc::new(Mutex::new(None::<T>));
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    let eq = Arc::clone(&eq);
+                    let last = Arc::clone(&last);
+                    async move {
+                        let mut guard = last.lock().await;
+                        if guard.as_ref().is_some_and(|prev| eq(prev, &value)) {
+                            return;
+                        }
+                        *guard = Some(value.clone());
+                        drop(guard);
+                        collector.emit(value).await;
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+/// Collect the whole upstream, let `arrange` reorder it, then emit it
+pub(super) fn buffer_all<T, A>(upstream: Flow<T>, arrange: A) -> Flow<T>
+where
+    T: Send + 'static,
+    A: Fn(&mut Vec<T>) + Send + Sync + 'static,
+{
+    let arrange = Arc::new(arrange);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let arrange = Arc::clone(&arrange);
+        async move {
+            let buffer = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let buffer_clone = Arc::clone(&buffer);
+            upstream
+                .collect(move |value| {
+                    let buffer = Arc::clone(&buffer_clone);
+                    async move {
+                        buffer.lock().unwrap().push(value);
+                    }
+                })
+                .await;
+
+            let mut values = std::mem::take(&mut *buffer.lock().unwrap());
+            arrange(&mut values);
+            for value in values {
+                collector.emit(value).await;
+            }
+        }
+    })
+}
+
+pub(super) fn drop_last<T>(upstream: Flow<T>, count: usize) -> Flow<T>
+where
+    T: Clone + Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            // The newest `count` values, held back until newer ones arrive
+            let held = Arc::new(Mutex::new(VecDeque::with_capacity(count + 1)));
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    let held = Arc::clone(&held);
+                    async move {
+                        let released = {
+                            let mut held = held.lock().await;
+                            held.push_back(value);
+                            if held.len() > count {
+                                held.pop_front()
+                            } else {
+                                None
+                            }
+                        };
+                        if let Some(value) = released {
+                            collector.emit(value).await;
+                        }
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn take_last<T>(upstream: Flow<T>, count: usize) -> Flow<T>
+where
+    T: Clone + Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            let tail = Arc::new(Mutex::new(VecDeque::with_capacity(count)));
+            let tail_clone = Arc::clone(&tail);
+            upstream
+                .collect(move |value| {
+                    let tail = Arc::clone(&tail_clone);
+                    async move {
+                        let mut tail = tail.lock().await;
+                        if count > 0 {
+                            if tail.len() == count {
+                                tail.pop_front();
+                            }
+                            tail.push_back(value);
+                        }
+                    }
+                })
+                .await;
+
+            let tail = std::mem::take(&mut *tail.lock().await);
+            for value in tail {
+                collector.emit(value).await;
+            }
+        }
+    })
+}
+
+pub(super) fn map_with_index<T, U, F>(upstream: Flow<T>, f: F) -> Flow<U>
+where
+    T: Send + 'static,
+    U: Send + 'static,
+    F: Fn(usize, T) -> U + Send + Sync + 'static,
+{
+    let f = Arc::new(f);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let f = Arc::clone(&f);
+        async move {
+            let index = Arc::new(AtomicUsize::new(0));
+            upstream
+                .collect(move |value| {
+                    let collector = collector.clone();
+                    let mapped = f(index.fetch_add(1, Ordering::Relaxed), value);
+                    async move {
+                        collector.emit(mapped).await;
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn cancel_on<T, F>(upstream: Flow<T>, predicate: F) -> Flow<T>
+where
+    T: Send + 'static,
+    F: Fn(&T) -> bool + Send + Sync + 'static,
+{
+    let predicate = Arc::new(predicate);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let predicate = Arc::clone(&predicate);
+        async move {
+            let stop = Arc::new(Notify::new());
+            let stop_clone = Arc::clone(&stop);
+
+            let collection = upstream.collect(move |value| {
+                let predicate = Arc::clone(&predicate);
+                let collector = collector.clone();
+                let stop = Arc::clone(&stop_clone);
+                async move {
+                    if predicate(&value) {
+                        stop.notify_one();
+                        // Park the upstream, even one that never yields, so
+                        // the select below gets to drop it
+                        std::future::pending::<()>().await;
+                    } else {
+                        collector.emit(value).await;
+                    }
+                }
+            });
+
+            tokio::select! {
+                biased;
+                _ = stop.notified() => {}
+                _ = collection => {}
+            }
+        }
+    })
+}
+
+pub(super) fn on_each_on<T, F, Fut>(upstream: Flow<T>, dispatcher: Dispatcher, f: F) -> Flow<T>
+where
+    T: Clone + Send + 'static,
+    F: Fn(&T) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let f = Arc::new(f);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let dispatcher = dispatcher.clone();
+        let f = Arc::clone(&f);
+        async move {
+            upstream
+                .collect(move |value| {
+                    let f = Arc::clone(&f);
+                    let dispatcher = dispatcher.clone();
+                    let collector = collector.clone();
+                    async move {
+                        let (done_tx, done_rx) = oneshot::channel();
+                        let observed = value.clone();
+                        let (effect, _guard) = AbortOnDrop::wrap(async move {
+                            f(&observed).await;
+                            let _ = done_tx.send(());
+                        });
+                        dispatcher.spawn(effect);
+
+                        // A panicking effect drops the sender; the value is still emitted
+                        let _ = done_rx.await;
+                        collector.emit(value).await;
+                    }
+                })
+                .await;
+        }
+    })
+}
+
+pub(super) fn take_until_notified<T>(upstream: Flow<T>, token: CancelToken) -> Flow<T>
+where
+    T: Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let token = token.clone();
+        async move {
+            let collection = upstream.collect(move |value| {
+                let collector = collector.clone();
+                async move {
+                    collector.emit(value).await;
+                }
+            });
+
+            tokio::select! {
+                biased;
+                _ = token.cancelled() => {}
+                _ = collection => {}
+            }
+        }
+    })
+}
+
+pub(super) fn chunked_by<T, K, F>(upstream: Flow<T>, key: F) -> Flow<Vec<T>>
+where
+    T: Send + 'static,
+    K: PartialEq + Send + 'static,
+    F: Fn(&T) -> K + Send + Sync + 'static,
+{
+    let key = Arc::new(key);
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        let key = Arc::clone(&key);
+        async move {
+            // Key of the current run and the values collected for it
+            let run = Arc::new(Mutex::new(None::<(K, Vec<T>)>));
+            let run_clone = Arc::clone(&run);
+            let collector_clone = collector.clone();
+
+            upstream
+                .collect(move |value| {
+                    let key = Arc::clone(&key);
+                    let collector = collector_clone.clone();
+                    let run = Arc::clone(&run_clone);
+                    async move {
+                        let value_key = key(&value);
+                        let mut guard = run.lock().await;
+                        let finished = match guard.as_mut() {
+                            Some((current, values)) if *current == value_key => {
+                                values.push(value);
+                                None
+                            }
+                            _ => guard
+                                .replace((value_key, vec![value]))
+                                .map(|(_, values)| values),
+                        };
+                        drop(guard);
+                        if let Some(values) = finished {
+                            collector.emit(values).await;
+                        }
+                    }
+                })
+                .await;
+
+            let last = run.lock().await.take();
+            if let Some((_, values)) = last {
+                collector.emit(values).await;
+            }
+        }
+    })
+}
+
+pub(super) fn run_length<T>(upstream: Flow<T>) -> Flow<(T, usize)>
+where
+    T: PartialEq + Send + 'static,
+{
+    Flow::new(move |collector| {
+        let upstream = upstream.clone();
+        async move {
+            // Value of the current run and how many times it was seen
+            let run = Arc::new(Mutex::new(None::<(T, usize)>));
+            let run_clone = Arc::clone(&run);
+            let collector_clone = collector.clone();
+
+            upstream
+                .collect(move |value| {
+                    let collector = collector_clone.clone();
+                    let run = Arc::clone(&run_clone);
+                    async move {
+                        let mut guard = run.lock().await;
+                        let finished = match guard.as_mut() {
+                            Some((current, count)) if *current == value => {
+                                *count += 1;
+                                None
+                            }
+                            _ => guard.replace((value, 1)),
+                        };
+                        drop(guard);
+                        if let Some(finished) = finished {
+                            collector.emit(finished).await;
+                        }
+                    }
+                })
+                .await;
+
+            let last = run.lock().await.take();
+            if let Some(last) = last {
+                collector.emit(last).await;
+            }
+        }
+    })
+}
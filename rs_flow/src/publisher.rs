@@ -0,0 +1,129 @@
+//! Broadcast channel with backpressure
+//!
+//! `tokio::sync::broadcast` overwrites its oldest value when the buffer is
+//! full, so a slow receiver lags and loses values. A `Publisher` can instead
+//! wait until the slowest receiver has made room, woken by its receivers
+//! rather than by polling.
+
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex, Notify};
+
+/// Sending half of the channel; clones send into the same channel
+pub(crate) struct Publisher<T> {
+    tx: broadcast::Sender<T>,
+    capacity: usize,
+    /// Notified whenever a receiver subscribes, takes a value or goes away
+    changed: Arc<Notify>,
+    /// Keeps concurrent waiting sends from overfilling the buffer together
+    send_lock: Arc<Mutex<()>>,
+}
+
+impl<T> Clone for Publisher<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            capacity: self.capacity,
+            changed: Arc::clone(&self.changed),
+            send_lock: Arc::clone(&self.send_lock),
+        }
+    }
+}
+
+impl<T> Publisher<T>
+where
+    T: Clone,
+{
+    pub(crate) fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        Self {
+            tx,
+            capacity,
+            changed: Arc::default(),
+            send_lock: Arc::default(),
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> Subscriber<T> {
+        let rx = self.tx.subscribe();
+        self.changed.notify_waiters();
+        Subscriber {
+            rx,
+            changed: NotifyOnDrop(Arc::clone(&self.changed)),
+        }
+    }
+
+    pub(crate) fn receiver_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Send without waiting, overwriting the oldest value if a receiver has
+    /// not taken it yet. Returns `false` if there are no receivers.
+    pub(crate) fn send_now(&self, value: T) -> bool {
+        self.tx.send(value).is_ok()
+    }
+
+    /// Send once every current receiver has room for the value. Returns
+    /// `false` without waiting if there are no receivers.
+    pub(crate) async fn send(&self, value: T) -> bool {
+        let _sending = self.send_lock.lock().await;
+        self.wait_for(|tx| tx.receiver_count() == 0 || tx.len() < self.capacity)
+            .await;
+        self.tx.send(value).is_ok()
+    }
+
+    /// Send once at least one receiver is subscribed and every receiver has
+    /// room for the value
+    pub(crate) async fn send_when_subscribed(&self, value: T) {
+        let _sending = self.send_lock.lock().await;
+        let mut value = value;
+        loop {
+            self.wait_for(|tx| tx.receiver_count() > 0 && tx.len() < self.capacity)
+                .await;
+            match self.tx.send(value) {
+                Ok(_) => return,
+                // The last receiver went away in the meantime; keep waiting
+                Err(broadcast::error::SendError(returned)) => value = returned,
+            }
+        }
+    }
+
+    async fn wait_for(&self, ready: impl Fn(&broadcast::Sender<T>) -> bool) {
+        loop {
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            // Registered before the check so a change in between is not missed
+            changed.as_mut().enable();
+            if ready(&self.tx) {
+                return;
+            }
+            changed.await;
+        }
+    }
+}
+
+/// Receiving half of the channel
+pub(crate) struct Subscriber<T> {
+    rx: broadcast::Receiver<T>,
+    /// Declared after `rx`, so waiting senders are woken only once the
+    /// receiver has released its unread values
+    changed: NotifyOnDrop,
+}
+
+impl<T> Subscriber<T>
+where
+    T: Clone,
+{
+    pub(crate) async fn recv(&mut self) -> Result<T, broadcast::error::RecvError> {
+        let result = self.rx.recv().await;
+        self.changed.0.notify_waiters();
+        result
+    }
+}
+
+struct NotifyOnDrop(Arc<Notify>);
+
+impl Drop for NotifyOnDrop {
+    fn drop(&mut self) {
+        self.0.notify_waiters();
+    }
+}
@@ -5,6 +5,10 @@ use rs_coroutine_core::Suspending;
 pub trait SuspendingExt<T> {
     /// Convert a Suspending into a Flow
     fn as_flow(&self) -> Flow<T>;
+
+    /// Convert a Suspending into a Flow that calls the block `n` times per
+    /// collection, emitting each result in turn (useful for polling)
+    fn as_flow_repeating(&self, n: usize) -> Flow<T>;
 }
 
 impl<T> SuspendingExt<T> for Suspending<T>
@@ -21,4 +25,38 @@ where
             }
         })
     }
+
+    fn as_flow_repeating(&self, n: usize) -> Flow<T> {
+        let suspending = self.clone();
+        Flow::from_fn(move |collector| {
+            let suspending = suspending.clone();
+            async move {
+                for _ in 0..n {
+                    let value = suspending.call().await;
+                    collector.emit(value).await;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::FlowTerminal;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_as_flow_repeating() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter_clone = Arc::clone(&counter);
+        let poll = Suspending::new(move || {
+            let counter = Arc::clone(&counter_clone);
+            Box::pin(async move { counter.fetch_add(1, Ordering::SeqCst) + 1 })
+        });
+
+        let values = poll.as_flow_repeating(3).to_vec().await;
+        assert_eq!(values, vec![1, 2, 3]);
+    }
 }
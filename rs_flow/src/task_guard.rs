@@ -0,0 +1,35 @@
+//! Drop guard for background tasks spawned by operators
+//!
+//! Operators that collect their upstream on a spawned task keep a guard for
+//! it, so the task is aborted if the collecting future is dropped before the
+//! flow completes instead of running on detached.
+
+use futures::future::{AbortHandle, Abortable};
+use std::future::Future;
+
+/// Aborts the guarded task when dropped
+pub(crate) struct AbortOnDrop(AbortHandle);
+
+impl AbortOnDrop {
+    /// Make `fut` abortable, returning it together with its guard.
+    /// Spawn the returned future and keep the guard alive while collecting.
+    pub(crate) fn wrap<F>(fut: F) -> (impl Future<Output = ()> + Send + 'static, Self)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let (handle, registration) = AbortHandle::new_pair();
+        let fut = Abortable::new(fut, registration);
+        (
+            async move {
+                let _ = fut.await;
+            },
+            Self(handle),
+        )
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
@@ -1,8 +1,10 @@
 use super::*;
-use std::collections::HashSet;
+use crate::operators::FlowExt;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+// Critical sections never span an await, so a std mutex avoids the async lock overhead
+use std::sync::Mutex;
 
 impl<T> FlowTerminal<T> for Flow<T>
 where
@@ -19,13 +21,13 @@ where
             let found = Arc::clone(&found_clone);
             async move {
                 if !found.swap(true, std::sync::atomic::Ordering::SeqCst) {
-                    *result.lock().await = Some(value);
+                    *result.lock().unwrap() = Some(value);
                 }
             }
         })
         .await;
 
-        let value = result.lock().await.take();
+        let value = result.lock().unwrap().take();
         value.ok_or(FlowError::Empty)
     }
 
@@ -45,7 +47,7 @@ where
             async move {
                 let prev = count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
                 if prev == 0 {
-                    *result.lock().await = Some(value);
+                    *result.lock().unwrap() = Some(value);
                 }
             }
         })
@@ -57,7 +59,7 @@ where
         } else if final_count > 1 {
             Err(FlowError::MoreThanOneElement)
         } else {
-            let value = result.lock().await.take();
+            let value = result.lock().unwrap().take();
             value.ok_or(FlowError::Empty)
         }
     }
@@ -73,33 +75,50 @@ where
         self.collect(move |value| {
             let result = Arc::clone(&result_clone);
             async move {
-                *result.lock().await = Some(value);
+                *result.lock().unwrap() = Some(value);
             }
         })
         .await;
 
-        let value = result.lock().await.take();
+        let value = result.lock().unwrap().take();
         value
     }
 
+    async fn last_or(self, default: T) -> T {
+        self.last_or_none().await.unwrap_or(default)
+    }
+
     async fn to_vec(self) -> Vec<T> {
-        let result = Arc::new(Mutex::new(Vec::new()));
+        self.to_vec_capacity(0).await
+    }
+
+    async fn to_vec_capacity(self, capacity: usize) -> Vec<T> {
+        let result = Arc::new(Mutex::new(Vec::with_capacity(capacity)));
         let result_clone = Arc::clone(&result);
 
         self.collect(move |value| {
             let result = Arc::clone(&result_clone);
             async move {
-                result.lock().await.push(value);
+                result.lock().unwrap().push(value);
             }
         })
         .await;
 
         Arc::try_unwrap(result)
             .ok()
-            .map(|m| m.into_inner())
+            .and_then(|m| m.into_inner().ok())
             .unwrap_or_default()
     }
 
+    async fn require_non_empty(self) -> Result<Vec<T>, FlowError> {
+        let values = self.to_vec().await;
+        if values.is_empty() {
+            Err(FlowError::Empty)
+        } else {
+            Ok(values)
+        }
+    }
+
     async fn to_set(self) -> HashSet<T>
     where
         T: Eq + Hash,
@@ -110,41 +129,85 @@ where
         self.collect(move |value| {
             let result = Arc::clone(&result_clone);
             async move {
-                result.lock().await.insert(value);
+                result.lock().unwrap().insert(value);
             }
         })
         .await;
 
         Arc::try_unwrap(result)
             .ok()
-            .map(|m| m.into_inner())
+            .and_then(|m| m.into_inner().ok())
             .unwrap_or_default()
     }
 
+    async fn collect_into(self, target: &mut Vec<T>) {
+        let target = Lent::new(target);
+        let shared = Arc::clone(&target.shared);
+
+        self.collect(move |value| {
+            let shared = Arc::clone(&shared);
+            async move {
+                shared.lock().unwrap().push(value);
+            }
+        })
+        .await;
+    }
+
+    async fn collect_into_set(self, target: &mut HashSet<T>)
+    where
+        T: Eq + Hash,
+    {
+        let target = Lent::new(target);
+        let shared = Arc::clone(&target.shared);
+
+        self.collect(move |value| {
+            let shared = Arc::clone(&shared);
+            async move {
+                shared.lock().unwrap().insert(value);
+            }
+        })
+        .await;
+    }
+
+    async fn collect_timed<F, Fut>(self, f: F) -> Duration
+    where
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let started = std::time::Instant::now();
+        self.collect(f).await;
+        started.elapsed()
+    }
+
+    async fn collect_for(self, duration: Duration) -> Vec<T> {
+        let mut values = Vec::new();
+        // A timed-out `collect_into` still leaves the gathered values in place
+        let _ = tokio::time::timeout(duration, self.collect_into(&mut values)).await;
+        values
+    }
+
     async fn fold<R, F>(self, initial: R, f: F) -> R
     where
         R: Send + 'static,
         F: FnMut(R, T) -> R + Send + 'static,
     {
-        let acc = Arc::new(Mutex::new(Some(initial)));
-        let f = Arc::new(Mutex::new(f));
-        let acc_clone = Arc::clone(&acc);
-        let f_clone = Arc::clone(&f);
+        // Accumulator and function share one lock so each value locks once
+        let state = Arc::new(Mutex::new((Some(initial), f)));
+        let state_clone = Arc::clone(&state);
 
         self.collect(move |value| {
-            let acc = Arc::clone(&acc_clone);
-            let f = Arc::clone(&f_clone);
+            let state = Arc::clone(&state_clone);
             async move {
-                let mut acc_guard = acc.lock().await;
-                let mut f_guard = f.lock().await;
-                if let Some(current) = acc_guard.take() {
-                    *acc_guard = Some(f_guard(current, value));
+                let mut guard = state.lock().unwrap();
+                let (acc, f) = &mut *guard;
+                if let Some(current) = acc.take() {
+                    *acc = Some(f(current, value));
                 }
             }
         })
         .await;
 
-        let value = acc.lock().await.take();
+        let value = state.lock().unwrap().0.take();
         value.expect("fold accumulator missing")
     }
 
@@ -152,26 +215,23 @@ where
     where
         F: FnMut(T, T) -> T + Send + 'static,
     {
-        let acc = Arc::new(Mutex::new(None::<T>));
-        let f = Arc::new(Mutex::new(f));
-        let acc_clone = Arc::clone(&acc);
-        let f_clone = Arc::clone(&f);
+        let state = Arc::new(Mutex::new((None::<T>, f)));
+        let state_clone = Arc::clone(&state);
 
         self.collect(move |value| {
-            let acc = Arc::clone(&acc_clone);
-            let f = Arc::clone(&f_clone);
+            let state = Arc::clone(&state_clone);
             async move {
-                let mut acc_guard = acc.lock().await;
-                let mut f_guard = f.lock().await;
-                *acc_guard = Some(match acc_guard.take() {
+                let mut guard = state.lock().unwrap();
+                let (acc, f) = &mut *guard;
+                *acc = Some(match acc.take() {
                     None => value,
-                    Some(current) => f_guard(current, value),
+                    Some(current) => f(current, value),
                 });
             }
         })
         .await;
 
-        let value = acc.lock().await.take();
+        let value = state.lock().unwrap().0.take();
         value.ok_or(FlowError::Empty)
     }
 
@@ -190,6 +250,13 @@ where
         count.load(std::sync::atomic::Ordering::SeqCst)
     }
 
+    async fn count_distinct(self) -> usize
+    where
+        T: Eq + Hash,
+    {
+        self.to_set().await.len()
+    }
+
     async fn any<F>(self, predicate: F) -> bool
     where
         F: FnMut(&T) -> bool + Send + 'static,
@@ -204,7 +271,7 @@ where
             let predicate = Arc::clone(&predicate_clone);
             async move {
                 if !found.load(std::sync::atomic::Ordering::SeqCst)
-                    && predicate.lock().await(&value)
+                    && predicate.lock().unwrap()(&value)
                 {
                     found.store(true, std::sync::atomic::Ordering::SeqCst);
                 }
@@ -229,7 +296,7 @@ where
             let predicate = Arc::clone(&predicate_clone);
             async move {
                 if all_match.load(std::sync::atomic::Ordering::SeqCst)
-                    && !predicate.lock().await(&value)
+                    && !predicate.lock().unwrap()(&value)
                 {
                     all_match.store(false, std::sync::atomic::Ordering::SeqCst);
                 }
@@ -246,6 +313,141 @@ where
     {
         !self.any(predicate).await
     }
+
+    async fn index_of<F>(self, predicate: F) -> Option<usize>
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        let index = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        // `take(1)` stops the upstream as soon as the first match arrives
+        self.map_sync(move |value| {
+            let current = index.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            (current, predicate(&value))
+        })
+        .filter_sync(|(_, matched)| *matched)
+        .take(1)
+        .first_or_none()
+        .await
+        .map(|(index, _)| index)
+    }
+
+    async fn count_while<F>(self, predicate: F) -> usize
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        // `cancel_on` stops the upstream at the first non-matching value
+        self.cancel_on(move |value| !predicate(value)).count().await
+    }
+
+    async fn sum_by<N, F>(self, selector: F) -> N
+    where
+        N: std::ops::Add<Output = N> + Default + Send + 'static,
+        F: Fn(&T) -> N + Send + 'static,
+    {
+        self.fold(N::default(), move |acc, value| acc + selector(&value))
+            .await
+    }
+
+    async fn min_max(self) -> Option<(T, T)>
+    where
+        T: Clone + PartialOrd,
+    {
+        self.fold(None, |acc: Option<(T, T)>, value| match acc {
+            None => Some((value.clone(), value)),
+            Some((min, max)) => {
+                let min = if value < min { value.clone() } else { min };
+                let max = if value > max { value } else { max };
+                Some((min, max))
+            }
+        })
+        .await
+    }
+
+    async fn index_of_max<K, F>(self, selector: F) -> Option<usize>
+    where
+        K: PartialOrd + Send + 'static,
+        F: Fn(&T) -> K + Send + 'static,
+    {
+        index_of_extreme(self, selector, |key, best| key > best).await
+    }
+
+    async fn index_of_min<K, F>(self, selector: F) -> Option<usize>
+    where
+        K: PartialOrd + Send + 'static,
+        F: Fn(&T) -> K + Send + 'static,
+    {
+        index_of_extreme(self, selector, |key, best| key < best).await
+    }
+}
+
+/// Index of the first value whose key is not beaten by any later key,
+/// where `beats(key, best)` decides whether `key` replaces the current best
+async fn index_of_extreme<T, K, F>(
+    flow: Flow<T>,
+    selector: F,
+    beats: fn(&K, &K) -> bool,
+) -> Option<usize>
+where
+    T: Send + 'static,
+    K: PartialOrd + Send + 'static,
+    F: Fn(&T) -> K + Send + 'static,
+{
+    // (index of the next value, best index and key so far)
+    flow.fold((0, None::<(usize, K)>), move |(index, best), value| {
+        let key = selector(&value);
+        let best = match best {
+            Some((_, ref best_key)) if !beats(&key, best_key) => best,
+            _ => Some((index, key)),
+        };
+        (index + 1, best)
+    })
+    .await
+    .1
+    .map(|(index, _)| index)
+}
+
+/// A caller's collection moved into shared state for the duration of a
+/// collection. The contents are moved back on drop, even if the collection
+/// is cancelled part-way.
+struct Lent<'a, C: Default> {
+    target: &'a mut C,
+    shared: Arc<Mutex<C>>,
+}
+
+impl<'a, C: Default> Lent<'a, C> {
+    fn new(target: &'a mut C) -> Self {
+        let shared = Arc::new(Mutex::new(std::mem::take(target)));
+        Self { target, shared }
+    }
+}
+
+impl<C: Default> Drop for Lent<'_, C> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        *self.target = std::mem::take(&mut *shared);
+    }
+}
+
+impl<K, V> Flow<(K, V)>
+where
+    K: Eq + Hash + Send + 'static,
+    V: Send + 'static,
+{
+    /// Collect key-value pairs into a HashMap. Later values overwrite earlier
+    /// ones with the same key.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let by_id = users.map_sync(|u| (u.id, u)).to_map().await;
+    /// ```
+    pub async fn to_map(self) -> HashMap<K, V> {
+        self.fold(HashMap::new(), |mut map, (key, value)| {
+            map.insert(key, value);
+            map
+        })
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -272,6 +474,18 @@ mod tests {
         assert!(matches!(result, Err(FlowError::Empty)));
     }
 
+    #[tokio::test]
+    async fn test_last_or() {
+        let numbers = flow(|c| async move {
+            c.emit(1).await;
+            c.emit(2).await;
+        });
+        assert_eq!(numbers.last_or(0).await, 2);
+
+        let empty: Flow<i32> = flow(|_c| async move {});
+        assert_eq!(empty.last_or(7).await, 7);
+    }
+
     #[tokio::test]
     async fn test_single() {
         let single_flow = flow(|c| async move {
@@ -341,6 +555,17 @@ mod tests {
         assert_eq!(count, 10);
     }
 
+    #[tokio::test]
+    async fn test_count_distinct() {
+        let numbers = flow(|c| async move {
+            for i in [1, 1, 2, 3, 3] {
+                c.emit(i).await;
+            }
+        });
+
+        assert_eq!(numbers.count_distinct().await, 3);
+    }
+
     #[tokio::test]
     async fn test_any() {
         let numbers = flow(|c| async move {
@@ -364,4 +589,229 @@ mod tests {
         let all_even = numbers.all(|x| *x % 2 == 0).await;
         assert!(all_even);
     }
+
+    #[tokio::test]
+    async fn test_index_of() {
+        let numbers = flow(|c| async move {
+            for i in [1, 3, 4, 5] {
+                c.emit(i).await;
+            }
+        });
+
+        assert_eq!(numbers.clone().index_of(|x| *x % 2 == 0).await, Some(2));
+        assert_eq!(numbers.index_of(|x| *x > 10).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_index_of_short_circuits() {
+        let naturals = flow(|c| async move {
+            let mut i = 0;
+            loop {
+                c.emit(i).await;
+                i += 1;
+                tokio::task::yield_now().await;
+            }
+        });
+
+        assert_eq!(naturals.index_of(|x| *x == 5).await, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_count_while() {
+        let numbers = flow(|c| async move {
+            for i in [2, 4, 5, 6] {
+                c.emit(i).await;
+            }
+        });
+        let is_even = |x: &i32| *x % 2 == 0;
+
+        assert_eq!(numbers.clone().count_while(is_even).await, 2);
+        assert_eq!(numbers.count_while(|x| *x > 10).await, 0);
+
+        // Stops at the first non-match of an endless 0, 1, 0, 1, ... upstream
+        let alternating = flow(|c| async move {
+            loop {
+                c.emit(0).await;
+                c.emit(1).await;
+            }
+        });
+        let counted = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            alternating.count_while(|x| *x == 0),
+        )
+        .await;
+        assert_eq!(counted, Ok(1));
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            FlowError::IndexOutOfBounds(3).to_string(),
+            "Flow completed before emitting a value at index 3"
+        );
+        assert_eq!(
+            FlowError::Timeout.to_string(),
+            "Flow did not complete within the timeout"
+        );
+    }
+
+    #[test]
+    fn test_error_equality() {
+        assert_eq!(
+            FlowError::IndexOutOfBounds(1),
+            FlowError::IndexOutOfBounds(1)
+        );
+        assert_ne!(
+            FlowError::IndexOutOfBounds(1),
+            FlowError::IndexOutOfBounds(2)
+        );
+        assert_eq!(FlowError::Timeout, FlowError::Timeout);
+        assert_ne!(FlowError::Timeout, FlowError::Empty);
+    }
+
+    #[tokio::test]
+    async fn test_sum_by() {
+        let words = flow(|c| async move {
+            c.emit("a").await;
+            c.emit("bbb").await;
+        });
+
+        let total = words.sum_by(|s| s.len()).await;
+        assert_eq!(total, 4);
+    }
+
+    #[tokio::test]
+    async fn test_min_max() {
+        let numbers = flow(|c| async move {
+            for i in [3, 1, 4, 1, 5] {
+                c.emit(i).await;
+            }
+        });
+        assert_eq!(numbers.min_max().await, Some((1, 5)));
+
+        let empty: Flow<i32> = flow(|_c| async move {});
+        assert_eq!(empty.min_max().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_collect_into_appends_to_existing_buffers() {
+        let first = flow(|c| async move {
+            for i in 1..=3 {
+                c.emit(i).await;
+            }
+        });
+        let second = flow(|c| async move {
+            for i in [3, 4] {
+                c.emit(i).await;
+            }
+        });
+
+        let mut buffer = Vec::with_capacity(16);
+        first.clone().collect_into(&mut buffer).await;
+        second.clone().collect_into(&mut buffer).await;
+        assert_eq!(buffer, vec![1, 2, 3, 3, 4]);
+        assert!(buffer.capacity() >= 16);
+
+        let mut seen = HashSet::new();
+        first.collect_into_set(&mut seen).await;
+        second.collect_into_set(&mut seen).await;
+        assert_eq!(seen, HashSet::from([1, 2, 3, 4]));
+    }
+
+    #[tokio::test]
+    async fn test_index_of_max_and_min() {
+        struct Reading {
+            sensor: &'static str,
+            value: f64,
+        }
+
+        let readings = flow(|c| async move {
+            for (sensor, value) in [("a", 3.0), ("b", 9.5), ("c", 1.0), ("d", 9.5), ("e", 1.0)] {
+                c.emit(Reading { sensor, value }).await;
+            }
+        });
+
+        let max = readings.clone().index_of_max(|r| r.value).await;
+        assert_eq!(max, Some(1));
+        let min = readings.clone().index_of_min(|r| r.value).await;
+        assert_eq!(min, Some(2));
+        let first_sensor = readings.index_of_min(|r| r.sensor).await;
+        assert_eq!(first_sensor, Some(0));
+
+        let empty = flow(|_c: crate::flow::FlowCollector<i32>| async move {});
+        assert_eq!(empty.index_of_max(|x| *x).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_collect_for_bounds_infinite_flow() {
+        let ticks = crate::builders::interval_flow(Duration::from_millis(10));
+        let values = ticks.collect_for(Duration::from_millis(55)).await;
+
+        assert!(!values.is_empty() && values.len() <= 7, "got {values:?}");
+        assert!(values.iter().copied().eq(0..values.len() as u64));
+
+        let finite = flow(|c| async move {
+            c.emit(1).await;
+            c.emit(2).await;
+        });
+        let started = std::time::Instant::now();
+        assert_eq!(finite.collect_for(Duration::from_secs(5)).await, vec![1, 2]);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_require_non_empty() {
+        let numbers = flow(|c| async move {
+            c.emit(1).await;
+            c.emit(2).await;
+        });
+        assert_eq!(numbers.require_non_empty().await, Ok(vec![1, 2]));
+
+        let empty = flow(|_c: crate::flow::FlowCollector<i32>| async move {});
+        assert_eq!(empty.require_non_empty().await, Err(FlowError::Empty));
+    }
+
+    #[tokio::test]
+    async fn test_to_vec_capacity_large_flow() {
+        let numbers = flow(|c| async move {
+            for i in 0..10_000 {
+                c.emit(i).await;
+            }
+        });
+
+        let result = numbers.to_vec_capacity(10_000).await;
+        assert_eq!(result.len(), 10_000);
+        assert!(result.capacity() >= 10_000);
+        assert!(result.iter().copied().eq(0..10_000));
+    }
+
+    #[tokio::test]
+    async fn test_terminals_high_throughput() {
+        let numbers = flow(|c| async move {
+            for i in 0..100_000u64 {
+                c.emit(i).await;
+            }
+        });
+
+        assert_eq!(numbers.clone().count().await, 100_000);
+        assert_eq!(
+            numbers.clone().fold(0u64, |acc, x| acc + x).await,
+            4_999_950_000
+        );
+        assert_eq!(numbers.clone().reduce(|a, b| a.max(b)).await, Ok(99_999));
+        let all = numbers.to_vec().await;
+        assert!(all.iter().copied().eq(0..100_000));
+    }
+
+    #[tokio::test]
+    async fn test_collect_timed() {
+        let slow = flow(|c| async move {
+            c.emit(1).await;
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            c.emit(2).await;
+        });
+
+        let elapsed = slow.collect_timed(|_| async {}).await;
+        assert!(elapsed >= Duration::from_millis(30));
+    }
 }
@@ -5,7 +5,9 @@
 
 use crate::flow::Flow;
 use std::collections::HashSet;
+use std::future::Future;
 use std::hash::Hash;
+use std::time::Duration;
 
 /// Error types for terminal operators
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -14,6 +16,10 @@ pub enum FlowError {
     Empty,
     /// Flow emitted more than one value when exactly one was expected
     MoreThanOneElement,
+    /// Flow completed before reaching the requested index
+    IndexOutOfBounds(usize),
+    /// Flow did not complete within the allotted time
+    Timeout,
 }
 
 impl std::fmt::Display for FlowError {
@@ -26,6 +32,14 @@ impl std::fmt::Display for FlowError {
                     "Flow emitted more than one value when exactly one was expected"
                 )
             }
+            FlowError::IndexOutOfBounds(index) => {
+                write!(
+                    f,
+                    "Flow completed before emitting a value at index {}",
+                    index
+                )
+            }
+            FlowError::Timeout => write!(f, "Flow did not complete within the timeout"),
         }
     }
 }
@@ -82,6 +96,14 @@ where
     /// ```
     async fn last_or_none(self) -> Option<T>;
 
+    /// Collect all values and return the last one, or `default` if empty.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let latest = readings.last_or(0.0).await;
+    /// ```
+    async fn last_or(self, default: T) -> T;
+
     /// Collect all values into a Vec.
     ///
     /// # Example
@@ -90,6 +112,24 @@ where
     /// ```
     async fn to_vec(self) -> Vec<T>;
 
+    /// Collect all values into a Vec pre-allocated for `capacity` values.
+    /// Useful for large flows of known size to avoid reallocations.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let list = flow_range(0, 10_000).to_vec_capacity(10_000).await;
+    /// ```
+    async fn to_vec_capacity(self, capacity: usize) -> Vec<T>;
+
+    /// Collect all values into a Vec, returning `FlowError::Empty` if the flow
+    /// completed without emitting any values.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let rows = query_rows().require_non_empty().await?;
+    /// ```
+    async fn require_non_empty(self) -> Result<Vec<T>, FlowError>;
+
     /// Collect all values into a HashSet.
     ///
     /// # Example
@@ -100,6 +140,45 @@ where
     where
         T: Eq + Hash;
 
+    /// Append all values to an existing Vec, reusing its allocation.
+    ///
+    /// # Example
+    /// ```ignore
+    /// buffer.clear();
+    /// batch.collect_into(&mut buffer).await;
+    /// ```
+    async fn collect_into(self, target: &mut Vec<T>);
+
+    /// Insert all values into an existing HashSet, reusing its allocation.
+    ///
+    /// # Example
+    /// ```ignore
+    /// events.map_sync(|e| e.user_id).collect_into_set(&mut seen).await;
+    /// ```
+    async fn collect_into_set(self, target: &mut HashSet<T>)
+    where
+        T: Eq + Hash;
+
+    /// Collect the flow with `f` and return how long the collection took.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let elapsed = flow.collect_timed(|x| async move { store(x).await }).await;
+    /// ```
+    async fn collect_timed<F, Fut>(self, f: F) -> Duration
+    where
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static;
+
+    /// Collect values into a Vec for at most `duration`, returning what was
+    /// gathered when the time elapses or the flow completes, whichever is first.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let sample = events.collect_for(Duration::from_secs(1)).await;
+    /// ```
+    async fn collect_for(self, duration: Duration) -> Vec<T>;
+
     /// Accumulate values using an initial value and an accumulator function.
     ///
     /// # Example
@@ -130,6 +209,16 @@ where
     /// ```
     async fn count(self) -> usize;
 
+    /// Count the number of distinct emitted values.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let visitors = page_views.map_sync(|v| v.user_id).count_distinct().await;
+    /// ```
+    async fn count_distinct(self) -> usize
+    where
+        T: Eq + Hash;
+
     /// Check if any value matches the predicate.
     ///
     /// # Example
@@ -159,6 +248,73 @@ where
     async fn none<F>(self, predicate: F) -> bool
     where
         F: FnMut(&T) -> bool + Send + 'static;
+
+    /// Return the zero-based index of the first value matching the predicate,
+    /// or `None` if no value matches. Stops collecting once a match is found.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let index = flow.index_of(|x| x % 2 == 0).await;
+    /// ```
+    async fn index_of<F>(self, predicate: F) -> Option<usize>
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static;
+
+    /// Count the leading values matching the predicate. Stops collecting at
+    /// the first value that does not match.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let warmup = samples.count_while(|s| s.is_warmup()).await;
+    /// ```
+    async fn count_while<F>(self, predicate: F) -> usize
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static;
+
+    /// Sum a value selected from each element, starting from `N::default()`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let total_len = words.sum_by(|s| s.len()).await;
+    /// ```
+    async fn sum_by<N, F>(self, selector: F) -> N
+    where
+        N: std::ops::Add<Output = N> + Default + Send + 'static,
+        F: Fn(&T) -> N + Send + 'static;
+
+    /// Return the minimum and maximum values in a single pass, or `None` if empty.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let (min, max) = flow.min_max().await.unwrap();
+    /// ```
+    async fn min_max(self) -> Option<(T, T)>
+    where
+        T: Clone + PartialOrd;
+
+    /// Return the index of the value with the largest selected key, or `None`
+    /// if empty. Ties keep the first such value.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let busiest = servers.index_of_max(|s| s.load).await;
+    /// ```
+    async fn index_of_max<K, F>(self, selector: F) -> Option<usize>
+    where
+        K: PartialOrd + Send + 'static,
+        F: Fn(&T) -> K + Send + 'static;
+
+    /// Return the index of the value with the smallest selected key, or `None`
+    /// if empty. Ties keep the first such value.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let cheapest = offers.index_of_min(|o| o.price).await;
+    /// ```
+    async fn index_of_min<K, F>(self, selector: F) -> Option<usize>
+    where
+        K: PartialOrd + Send + 'static,
+        F: Fn(&T) -> K + Send + 'static;
 }
 
 mod implementation;
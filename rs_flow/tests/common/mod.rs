@@ -0,0 +1,43 @@
+//! Helpers shared by the integration tests
+
+use coroflow::{Dispatcher, Executor};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::runtime::{Builder, Handle, Runtime};
+
+struct PoolExecutor(Handle);
+
+impl Executor for PoolExecutor {
+    fn spawn(&self, fut: Pin<Box<dyn Future<Output = ()> + Send + 'static>>) {
+        self.0.spawn(fut);
+    }
+}
+
+/// A dispatcher backed by its own single-threaded pool, plus a separate
+/// runtime to drive the test from
+pub struct Pool {
+    /// Spawns onto the pool's worker thread
+    pub dispatcher: Dispatcher,
+    /// Current-thread runtime for the test body
+    pub main: Runtime,
+    /// Dropping the pool stops `dispatcher`, so keep the whole `Pool` bound
+    _pool: Runtime,
+}
+
+/// Start a pool whose worker thread is named `thread_name`
+pub fn pool(thread_name: &str) -> Pool {
+    let pool = Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name(thread_name)
+        .enable_all()
+        .build()
+        .unwrap();
+    let dispatcher = Dispatcher::new(Arc::new(PoolExecutor(pool.handle().clone())));
+    let main = Builder::new_current_thread().enable_all().build().unwrap();
+    Pool {
+        dispatcher,
+        main,
+        _pool: pool,
+    }
+}
@@ -1,7 +1,11 @@
-use coroflow::{flow, FlowExt};
+use coroflow::{
+    empty_flow, flow, flow_of, flow_range, CancelToken, FlowExt, FlowTerminal, Notification,
+};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+mod common;
+
 #[tokio::test]
 async fn map_and_filter_pipeline_produces_expected_values() {
     let numbers = flow(|collector| async move {
@@ -52,3 +56,762 @@ async fn drop_and_take_limit_flow_size() {
     let final_values = results.lock().await.clone();
     assert_eq!(final_values, vec![3, 4]);
 }
+
+#[tokio::test]
+async fn distinct_until_changed_by_async_collapses_same_keys() {
+    let values = flow(|collector| async move {
+        for value in [1, 3, 2, 4, 6, 5] {
+            collector.emit(value).await;
+        }
+    })
+    .distinct_until_changed_by_async(|value| {
+        let value = *value;
+        async move {
+            tokio::task::yield_now().await;
+            value % 2
+        }
+    })
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![1, 2, 5]);
+}
+
+#[tokio::test]
+async fn on_first_and_on_last_fire_once() {
+    let firsts = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let lasts = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let firsts_clone = Arc::clone(&firsts);
+    let lasts_clone = Arc::clone(&lasts);
+
+    let values = flow(|collector| async move {
+        for value in 1..=4 {
+            collector.emit(value).await;
+        }
+    })
+    .on_first(move |value| firsts_clone.lock().unwrap().push(*value))
+    .on_last(move |value| lasts_clone.lock().unwrap().push(*value))
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![1, 2, 3, 4]);
+    assert_eq!(*firsts.lock().unwrap(), vec![1]);
+    assert_eq!(*lasts.lock().unwrap(), vec![4]);
+}
+
+#[tokio::test]
+async fn materialize_and_dematerialize_round_trip() {
+    let numbers = flow(|collector| async move {
+        collector.emit(1).await;
+        collector.emit(2).await;
+    });
+
+    let events = numbers.clone().materialize().to_vec().await;
+    assert_eq!(
+        events,
+        vec![
+            Notification::Value(1),
+            Notification::Value(2),
+            Notification::Complete
+        ]
+    );
+
+    let values = numbers.materialize().dematerialize().to_vec().await;
+    assert_eq!(values, vec![1, 2]);
+}
+
+#[tokio::test]
+async fn dematerialize_stops_collecting_at_complete() {
+    // Keeps emitting after `Complete` and never yields on its own
+    let notifications = flow(|collector| async move {
+        collector.emit(Notification::Value(1)).await;
+        collector.emit(Notification::Complete).await;
+        let mut value = 2;
+        loop {
+            collector.emit(Notification::Value(value)).await;
+            value += 1;
+        }
+    });
+
+    let values = tokio::time::timeout(
+        std::time::Duration::from_secs(1),
+        notifications.dematerialize().to_vec(),
+    )
+    .await
+    .expect("dematerialize should complete at Complete");
+    assert_eq!(values, vec![1]);
+}
+
+#[tokio::test]
+async fn on_each_parallel_preserves_order() {
+    let completed = Arc::new(Mutex::new(Vec::new()));
+    let completed_clone = Arc::clone(&completed);
+
+    let values = flow(|collector| async move {
+        for value in 1..=4u64 {
+            collector.emit(value).await;
+        }
+    })
+    .on_each_parallel(4, move |value| {
+        let value = *value;
+        let completed = Arc::clone(&completed_clone);
+        async move {
+            // Later values finish their side effects first
+            tokio::time::sleep(std::time::Duration::from_millis((5 - value) * 15)).await;
+            completed.lock().await.push(value);
+        }
+    })
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![1, 2, 3, 4]);
+    assert_eq!(*completed.lock().await, vec![4, 3, 2, 1]);
+}
+
+#[tokio::test]
+async fn dropping_on_each_parallel_collection_stops_producer_and_effects() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    let produced = Arc::new(AtomicUsize::new(0));
+    let effects = Arc::new(AtomicUsize::new(0));
+    let produced_clone = Arc::clone(&produced);
+    let effects_clone = Arc::clone(&effects);
+    let parallel = flow(move |collector| {
+        let produced = Arc::clone(&produced_clone);
+        async move {
+            loop {
+                produced.fetch_add(1, Ordering::SeqCst);
+                collector.emit(()).await;
+            }
+        }
+    })
+    .on_each_parallel(4, move |_| {
+        let effects = Arc::clone(&effects_clone);
+        async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            effects.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+
+    let _ = tokio::time::timeout(Duration::from_millis(25), parallel.collect(|_| async {})).await;
+
+    tokio::task::yield_now().await;
+    let produced_after_drop = produced.load(Ordering::SeqCst);
+    let effects_after_drop = effects.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    assert_eq!(produced.load(Ordering::SeqCst), produced_after_drop);
+    assert_eq!(effects.load(Ordering::SeqCst), effects_after_drop);
+}
+
+#[tokio::test]
+async fn scan_async_emits_each_accumulator() {
+    let sums = flow(|collector| async move {
+        for value in 1..=4 {
+            collector.emit(value).await;
+        }
+    })
+    .scan_async(0, |acc, value| async move {
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        acc + value
+    })
+    .to_vec()
+    .await;
+
+    assert_eq!(sums, vec![0, 1, 3, 6, 10]);
+}
+
+#[tokio::test]
+async fn filter_map_keeps_only_some_results() {
+    let parsed = flow(|collector| async move {
+        for value in ["1", "x", "3"] {
+            collector.emit(value).await;
+        }
+    })
+    .filter_map(|value| value.parse::<i32>().ok())
+    .to_vec()
+    .await;
+
+    assert_eq!(parsed, vec![1, 3]);
+}
+
+#[tokio::test]
+async fn filter_map_async_drops_failed_lookups() {
+    let names = flow(|collector| async move {
+        for id in 1..=4 {
+            collector.emit(id).await;
+        }
+    })
+    .filter_map_async(|id| async move {
+        tokio::task::yield_now().await;
+        match id {
+            1 => Some("alice"),
+            3 => Some("carol"),
+            _ => None,
+        }
+    })
+    .to_vec()
+    .await;
+
+    assert_eq!(names, vec!["alice", "carol"]);
+}
+
+#[tokio::test]
+async fn default_if_empty_only_applies_to_empty_flows() {
+    let defaulted = empty_flow::<i32>().default_if_empty(-1).to_vec().await;
+    assert_eq!(defaulted, vec![-1]);
+
+    let untouched = flow(|collector| async move {
+        collector.emit(1).await;
+        collector.emit(2).await;
+    })
+    .default_if_empty(-1)
+    .to_vec()
+    .await;
+    assert_eq!(untouched, vec![1, 2]);
+}
+
+#[tokio::test]
+async fn yielding_lets_other_tasks_progress_during_busy_emission() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let ticks_clone = Arc::clone(&ticks);
+    let ticker = tokio::spawn(async move {
+        loop {
+            ticks_clone.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+        }
+    });
+
+    let busy = flow(|collector| async move {
+        for value in 0..100 {
+            collector.emit(value).await;
+        }
+    });
+
+    let observed = Arc::new(AtomicUsize::new(0));
+    let observed_clone = Arc::clone(&observed);
+    let ticks_clone = Arc::clone(&ticks);
+    busy.yielding()
+        .collect(move |_| {
+            let observed = Arc::clone(&observed_clone);
+            let ticks = Arc::clone(&ticks_clone);
+            async move {
+                observed.store(ticks.load(Ordering::SeqCst), Ordering::SeqCst);
+            }
+        })
+        .await;
+    ticker.abort();
+
+    assert!(observed.load(Ordering::SeqCst) > 0);
+}
+
+#[tokio::test]
+async fn with_previous_pairs_each_value_with_its_predecessor() {
+    let pairs = flow(|collector| async move {
+        for value in 1..=3 {
+            collector.emit(value).await;
+        }
+    })
+    .with_previous()
+    .to_vec()
+    .await;
+
+    assert_eq!(pairs, vec![(None, 1), (Some(1), 2), (Some(2), 3)]);
+}
+
+#[test]
+fn flow_on_moves_only_the_upstream() {
+    use std::thread;
+
+    let pool = common::pool("flow-on-pool");
+
+    let producer_threads = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&producer_threads);
+    let source = flow(move |collector| {
+        let recorded = Arc::clone(&recorded);
+        async move {
+            for value in 0..3 {
+                let name = thread::current().name().map(str::to_owned);
+                recorded.lock().unwrap().push(name);
+                collector.emit(value).await;
+            }
+        }
+    });
+
+    let collected = pool.main.block_on(
+        source
+            .flow_on(pool.dispatcher.clone())
+            .map_sync(|value| (value, thread::current().id()))
+            .to_vec(),
+    );
+
+    assert_eq!(
+        *producer_threads.lock().unwrap(),
+        vec![Some("flow-on-pool".to_string()); 3]
+    );
+    assert_eq!(
+        collected,
+        (0..3)
+            .map(|value| (value, thread::current().id()))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn dropping_flow_on_collection_stops_the_producer() {
+    use coroflow::Dispatchers;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    let produced = Arc::new(AtomicUsize::new(0));
+    let produced_clone = Arc::clone(&produced);
+    let infinite = flow(move |collector| {
+        let produced = Arc::clone(&produced_clone);
+        async move {
+            loop {
+                produced.fetch_add(1, Ordering::SeqCst);
+                collector.emit(()).await;
+                tokio::task::yield_now().await;
+            }
+        }
+    });
+
+    let shifted = infinite.flow_on(Dispatchers::main());
+    let _ = tokio::time::timeout(Duration::from_millis(20), shifted.collect(|_| async {})).await;
+
+    tokio::task::yield_now().await;
+    let after_drop = produced.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(produced.load(Ordering::SeqCst), after_drop);
+}
+
+#[tokio::test]
+async fn share_multicasts_a_single_upstream_run() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let runs_clone = Arc::clone(&runs);
+    let shared = flow(move |collector| {
+        runs_clone.fetch_add(1, Ordering::SeqCst);
+        async move {
+            for value in 1..=5 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                collector.emit(value).await;
+            }
+        }
+    })
+    .share();
+
+    let (first, second) = tokio::join!(shared.clone().to_vec(), shared.clone().to_vec());
+
+    assert_eq!(first, vec![1, 2, 3, 4, 5]);
+    assert_eq!(second, vec![1, 2, 3, 4, 5]);
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn share_delivers_long_sequences_to_slow_collectors() {
+    let shared = flow_range(0..1000).share();
+
+    let slow = shared.clone().map(|value| async move {
+        tokio::task::yield_now().await;
+        value
+    });
+    let (fast, slow) = tokio::join!(shared.to_vec(), slow.to_vec());
+
+    assert_eq!(fast, (0..1000).collect::<Vec<_>>());
+    assert_eq!(slow, (0..1000).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn forward_to_emits_every_value_into_shared_flow() {
+    use coroflow::{CoroutineScope, Dispatchers, SharedFlow};
+
+    let scope = CoroutineScope::new(Dispatchers::main());
+    let target = SharedFlow::<i32>::new(8);
+
+    let subscriber = tokio::spawn(target.as_flow().take(3).to_vec());
+    while target.subscriber_count() == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    let job = flow_of(vec![1, 2, 3]).forward_to(&scope, target.clone());
+    job.join().await;
+
+    assert_eq!(subscriber.await.unwrap(), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn cancel_on_completes_before_the_matching_value() {
+    let values = flow(|collector| async move {
+        for value in 1..=4 {
+            collector.emit(value).await;
+        }
+    })
+    .cancel_on(|value| *value == 3)
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![1, 2]);
+}
+
+#[tokio::test]
+async fn cancel_on_stops_an_infinite_upstream_that_never_yields() {
+    let values = flow(|collector| async move {
+        let mut value = 0;
+        loop {
+            collector.emit(value).await;
+            value += 1;
+        }
+    })
+    .cancel_on(|value| *value == 3);
+
+    let values = tokio::time::timeout(std::time::Duration::from_secs(1), values.to_vec())
+        .await
+        .expect("cancel_on should stop the upstream");
+    assert_eq!(values, vec![0, 1, 2]);
+}
+
+#[tokio::test]
+async fn chunked_by_groups_consecutive_equal_keys() {
+    let runs = flow(|collector| async move {
+        for value in [1, 1, 2, 3, 3, 3] {
+            collector.emit(value).await;
+        }
+    })
+    .chunked_by(|value| *value)
+    .to_vec()
+    .await;
+
+    assert_eq!(runs, vec![vec![1, 1], vec![2], vec![3, 3, 3]]);
+}
+
+#[tokio::test]
+async fn moving_average_over_sliding_window() {
+    let readings = flow(|collector| async move {
+        for value in [1.0, 2.0, 3.0, 4.0, 5.0] {
+            collector.emit(value).await;
+        }
+    });
+
+    let full = readings.clone().moving_average(3, false).to_vec().await;
+    assert_eq!(full, vec![2.0, 3.0, 4.0]);
+
+    let partial = readings.moving_average(3, true).to_vec().await;
+    assert_eq!(partial, vec![1.0, 1.5, 2.0, 3.0, 4.0]);
+}
+
+#[tokio::test]
+async fn map_with_index_matches_index_then_map_chain() {
+    let numbers = flow(|collector| async move {
+        for value in 0..10_000u64 {
+            collector.emit(value * 3).await;
+        }
+    });
+
+    let fused = numbers
+        .clone()
+        .map_with_index(|index, value| index as u64 + value)
+        .to_vec()
+        .await;
+    let chained = numbers
+        .scan_async((None::<usize>, 0u64), |(index, _), value| async move {
+            (Some(index.map_or(0, |i| i + 1)), value)
+        })
+        .filter_map(|(index, value)| index.map(|index| index as u64 + value))
+        .to_vec()
+        .await;
+
+    assert_eq!(fused.len(), 10_000);
+    assert_eq!(fused, chained);
+    assert_eq!(fused[..3], [0, 4, 8]);
+}
+
+#[tokio::test]
+async fn take_until_notified_stops_infinite_flow() {
+    let ticks = flow(|collector| async move {
+        let mut tick = 0u64;
+        loop {
+            collector.emit(tick).await;
+            tick += 1;
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        }
+    });
+
+    let token = CancelToken::new();
+    let canceller = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+        canceller.cancel();
+    });
+
+    let values = tokio::time::timeout(
+        std::time::Duration::from_secs(1),
+        ticks.take_until_notified(token).to_vec(),
+    )
+    .await
+    .expect("collection should terminate once the token is cancelled");
+    assert!(!values.is_empty());
+    assert!(values.windows(2).all(|pair| pair[1] == pair[0] + 1));
+}
+
+#[test]
+fn on_each_on_runs_side_effect_on_dispatcher_pool() {
+    let pool = common::pool("side-effect-pool");
+    let dispatcher = pool.dispatcher.clone();
+
+    let effect_threads = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let effect_threads_clone = Arc::clone(&effect_threads);
+    let values = pool.main.block_on(async move {
+        flow(|collector| async move {
+            for value in 1..=3 {
+                collector.emit(value).await;
+            }
+        })
+        .on_each_on(dispatcher, move |_| {
+            let effect_threads = Arc::clone(&effect_threads_clone);
+            async move {
+                let name = std::thread::current().name().map(str::to_owned);
+                effect_threads.lock().unwrap().push(name);
+            }
+        })
+        .map_sync(|value| (value, std::thread::current().name().map(str::to_owned)))
+        .to_vec()
+        .await
+    });
+
+    let caller = std::thread::current().name().map(str::to_owned);
+    assert_eq!(
+        values,
+        (1..=3)
+            .map(|value| (value, caller.clone()))
+            .collect::<Vec<_>>()
+    );
+    let effect_threads = effect_threads.lock().unwrap();
+    assert_eq!(effect_threads.len(), 3);
+    assert!(effect_threads
+        .iter()
+        .all(|name| name.as_deref() == Some("side-effect-pool")));
+}
+
+#[tokio::test]
+async fn assert_ordered_panics_on_out_of_order_value() {
+    let ordered = flow(|collector| async move {
+        for value in [1, 2, 2, 5] {
+            collector.emit(value).await;
+        }
+    });
+    assert_eq!(ordered.assert_ordered().to_vec().await, vec![1, 2, 2, 5]);
+
+    let unordered = flow(|collector| async move {
+        for value in [1, 3, 2] {
+            collector.emit(value).await;
+        }
+    });
+    let result = tokio::spawn(async move { unordered.assert_ordered().to_vec().await }).await;
+    assert!(result.unwrap_err().is_panic());
+}
+
+#[tokio::test]
+async fn drop_last_omits_final_values() {
+    let numbers = flow(|collector| async move {
+        for value in 1..=5 {
+            collector.emit(value).await;
+        }
+    });
+
+    assert_eq!(numbers.clone().drop_last(2).to_vec().await, vec![1, 2, 3]);
+    assert_eq!(
+        numbers.clone().drop_last(0).to_vec().await,
+        vec![1, 2, 3, 4, 5]
+    );
+    assert!(numbers.drop_last(10).to_vec().await.is_empty());
+}
+
+#[tokio::test]
+async fn take_last_emits_tail_after_completion() {
+    let numbers = flow(|collector| async move {
+        for value in 1..=4 {
+            collector.emit(value).await;
+        }
+    });
+    assert_eq!(numbers.take_last(2).to_vec().await, vec![3, 4]);
+
+    let short = flow(|collector| async move {
+        for value in 1..=3 {
+            collector.emit(value).await;
+        }
+    });
+    assert_eq!(short.clone().take_last(10).to_vec().await, vec![1, 2, 3]);
+    assert!(short.take_last(0).to_vec().await.is_empty());
+}
+
+#[tokio::test]
+async fn scan_complete_emits_summary_last() {
+    let numbers = flow(|collector| async move {
+        for value in 1..=4 {
+            collector.emit(value).await;
+        }
+    });
+
+    let values = numbers
+        .scan_complete(
+            0,
+            |acc, value| acc + value,
+            |total, collector| async move {
+                collector.emit(total * 100).await;
+            },
+        )
+        .to_vec()
+        .await;
+
+    assert_eq!(values, vec![0, 1, 3, 6, 10, 1000]);
+}
+
+#[tokio::test]
+async fn distinct_until_changed_with_collapses_near_equal_floats() {
+    let readings = flow(|collector| async move {
+        for value in [1.0, 1.05, 1.08, 2.0, 2.01, 1.0] {
+            collector.emit(value).await;
+        }
+    });
+
+    let values = readings
+        .distinct_until_changed_with(|prev: &f64, curr: &f64| (prev - curr).abs() < 0.1)
+        .to_vec()
+        .await;
+
+    assert_eq!(values, vec![1.0, 2.0, 1.0]);
+}
+
+#[tokio::test]
+async fn distinct_recent_suppresses_repeats_within_window() {
+    let values = flow_of(vec![1, 2, 1, 3, 1])
+        .distinct_recent(2)
+        .to_vec()
+        .await;
+
+    assert_eq!(values, vec![1, 2, 3, 1]);
+}
+
+#[tokio::test]
+async fn buffer_all_drains_upstream_before_emitting() {
+    let upstream_done = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let upstream_done_clone = Arc::clone(&upstream_done);
+    let numbers = flow(move |collector| {
+        let upstream_done = Arc::clone(&upstream_done_clone);
+        async move {
+            for value in [3, 1, 2] {
+                collector.emit(value).await;
+            }
+            upstream_done.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    });
+
+    let observed = numbers
+        .clone()
+        .buffer_all()
+        .map_sync(move |value| {
+            (
+                value,
+                upstream_done.load(std::sync::atomic::Ordering::SeqCst),
+            )
+        })
+        .to_vec()
+        .await;
+    assert_eq!(observed, vec![(3, true), (1, true), (2, true)]);
+
+    assert_eq!(numbers.sorted().to_vec().await, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn sorted_by_and_sorted_by_key_order_structs() {
+    struct User {
+        name: &'static str,
+        age: u32,
+    }
+
+    let users = flow(|collector| async move {
+        for (name, age) in [("carol", 41), ("alice", 29), ("bob", 35), ("dave", 29)] {
+            collector.emit(User { name, age }).await;
+        }
+    });
+
+    let by_age = users.clone().sorted_by_key(|user| user.age).to_vec().await;
+    assert_eq!(
+        by_age.iter().map(|user| user.name).collect::<Vec<_>>(),
+        vec!["alice", "dave", "bob", "carol"]
+    );
+
+    let by_name_desc = users
+        .sorted_by(|a, b| b.name.cmp(a.name))
+        .map_sync(|user| user.name)
+        .to_vec()
+        .await;
+    assert_eq!(by_name_desc, vec!["dave", "carol", "bob", "alice"]);
+}
+
+#[tokio::test]
+async fn run_length_counts_consecutive_values() {
+    let values = flow(|collector| async move {
+        for value in [1, 1, 2, 3, 3, 3] {
+            collector.emit(value).await;
+        }
+    })
+    .run_length()
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![(1, 2), (2, 1), (3, 3)]);
+}
+
+/// Count the values of `flow` on a fresh thread with a small, fixed stack
+fn count_on_small_stack(flow: coroflow::Flow<u64>) -> usize {
+    std::thread::Builder::new()
+        .stack_size(1 << 20)
+        .spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(flow.count())
+        })
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+fn chained(mut numbers: coroflow::Flow<u64>, depth: usize) -> coroflow::Flow<u64> {
+    for _ in 0..depth {
+        numbers = numbers.map_sync(|x| x + 1).filter_sync(|_| true);
+    }
+    numbers
+}
+
+#[test]
+fn operator_chain_stack_does_not_grow_per_value() {
+    let many = flow(|collector| async move {
+        for value in 0..200_000u64 {
+            collector.emit(value).await;
+        }
+    });
+
+    assert_eq!(count_on_small_stack(chained(many, 20)), 200_000);
+}
+
+#[test]
+fn buffer_bounds_stack_depth_of_long_chains() {
+    let mut numbers = flow(|collector| async move {
+        for value in 0..1_000u64 {
+            collector.emit(value).await;
+        }
+    });
+    // Too deep to poll inline on a 1 MiB stack in debug builds; `buffer` splits it into tasks
+    for _ in 0..20 {
+        numbers = chained(numbers, 50).buffer(16);
+    }
+
+    assert_eq!(count_on_small_stack(numbers), 1_000);
+}
@@ -0,0 +1,377 @@
+use coroflow::{flow, BufferOverflow, FlowExt, FlowTerminal};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::sleep;
+
+mod common;
+
+#[tokio::test]
+async fn chunked_timeout_flushes_on_size_and_time() {
+    let batches = flow(|collector| async move {
+        // Burst: flushed by size
+        for value in 1..=3 {
+            collector.emit(value).await;
+        }
+        // Slow emission: flushed by time
+        collector.emit(4).await;
+        sleep(Duration::from_millis(80)).await;
+        // Partial batch: flushed on completion
+        collector.emit(5).await;
+    })
+    .chunked_timeout(3, Duration::from_millis(30))
+    .to_vec()
+    .await;
+
+    assert_eq!(batches, vec![vec![1, 2, 3], vec![4], vec![5]]);
+}
+
+#[tokio::test]
+async fn throttle_passes_only_window_leading_values() {
+    let values = flow(|collector| async move {
+        for value in 0..5 {
+            collector.emit(value).await;
+        }
+        sleep(Duration::from_millis(80)).await;
+        for value in 5..10 {
+            collector.emit(value).await;
+        }
+    })
+    .throttle(Duration::from_millis(40))
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![0, 5]);
+}
+
+#[tokio::test]
+async fn throttle_leading_trailing_emits_both_window_edges() {
+    let values = flow(|collector| async move {
+        for value in 0..5 {
+            collector.emit(value).await;
+        }
+        sleep(Duration::from_millis(120)).await;
+        collector.emit(5).await;
+    })
+    .throttle_leading_trailing(Duration::from_millis(40))
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![0, 4, 5]);
+}
+
+#[tokio::test]
+async fn distinct_window_drops_repeats_until_window_expires() {
+    let values = flow(|collector| async move {
+        collector.emit(1).await;
+        collector.emit(1).await;
+        collector.emit(2).await;
+        sleep(Duration::from_millis(60)).await;
+        collector.emit(1).await;
+        collector.emit(2).await;
+    })
+    .distinct_window(Duration::from_millis(40))
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![1, 2, 1, 2]);
+}
+
+#[tokio::test]
+async fn debounce_emits_last_value_of_each_burst() {
+    let values = flow(|collector| async move {
+        for value in 0..3 {
+            collector.emit(value).await;
+        }
+        sleep(Duration::from_millis(80)).await;
+        for value in 3..6 {
+            collector.emit(value).await;
+        }
+    })
+    .debounce(Duration::from_millis(40))
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![2, 5]);
+}
+
+#[tokio::test]
+async fn keepalive_repeats_last_value_while_upstream_stalls() {
+    let values = flow(|collector| async move {
+        collector.emit(1).await;
+        sleep(Duration::from_millis(100)).await;
+        collector.emit(2).await;
+    })
+    .keepalive(Duration::from_millis(30))
+    .to_vec()
+    .await;
+
+    let repeats = values.iter().filter(|value| **value == 1).count();
+    assert!(repeats >= 3, "expected repeated 1s, got {values:?}");
+    assert!(values[..repeats].iter().all(|value| *value == 1));
+    assert_eq!(values[repeats..], [2]);
+}
+
+#[tokio::test]
+async fn heartbeat_sample_emits_none_for_idle_windows() {
+    let samples = flow(|collector| async move {
+        collector.emit(1).await;
+        sleep(Duration::from_millis(5)).await;
+        collector.emit(2).await;
+        sleep(Duration::from_millis(95)).await;
+        collector.emit(3).await;
+        sleep(Duration::from_millis(40)).await;
+    })
+    .heartbeat_sample(Duration::from_millis(40))
+    .to_vec()
+    .await;
+
+    assert_eq!(samples, vec![Some(2), None, Some(3)]);
+}
+
+#[tokio::test]
+async fn metrics_every_reports_per_window_counts() {
+    let ticks = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&ticks);
+    let values = flow(|collector| async move {
+        for value in 0..20 {
+            collector.emit(value).await;
+            sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .metrics_every(Duration::from_millis(25), move |count| {
+        recorded.lock().unwrap().push(count)
+    })
+    .to_vec()
+    .await;
+
+    assert_eq!(values, (0..20).collect::<Vec<_>>());
+    let ticks = ticks.lock().unwrap().clone();
+    assert!(ticks.len() >= 3, "expected several windows, got {ticks:?}");
+    assert_eq!(ticks.iter().sum::<usize>(), 20);
+    assert!(ticks.iter().all(|count| *count <= 10), "{ticks:?}");
+}
+
+#[tokio::test]
+async fn dropping_debounced_collection_stops_producer() {
+    let produced = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&produced);
+    let ticks = flow(move |collector| {
+        let counter = Arc::clone(&counter);
+        async move {
+            loop {
+                counter.fetch_add(1, Ordering::SeqCst);
+                collector.emit(()).await;
+                sleep(Duration::from_millis(2)).await;
+            }
+        }
+    });
+
+    let collected = ticks.debounce(Duration::from_millis(50)).to_vec();
+    assert!(tokio::time::timeout(Duration::from_millis(30), collected)
+        .await
+        .is_err());
+
+    let stopped_at = produced.load(Ordering::SeqCst);
+    assert!(stopped_at > 0);
+    sleep(Duration::from_millis(30)).await;
+    assert_eq!(produced.load(Ordering::SeqCst), stopped_at);
+}
+
+#[test]
+fn timers_fire_when_pinned_to_custom_dispatcher() {
+    use coroflow::{interval_flow_on, TimingOptions};
+
+    let pool = common::pool("timer-pool");
+    let timers = pool.dispatcher.clone();
+
+    let thread_name = || std::thread::current().name().map(str::to_owned);
+    let upstream_threads = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let upstream_threads_clone = Arc::clone(&upstream_threads);
+    let (debounced, ticks) = pool.main.block_on(async move {
+        let debounced = flow(move |collector| {
+            let upstream_threads = Arc::clone(&upstream_threads_clone);
+            async move {
+                for value in 0..6 {
+                    if value == 3 {
+                        sleep(Duration::from_millis(80)).await;
+                    }
+                    upstream_threads.lock().unwrap().push(thread_name());
+                    collector.emit(value).await;
+                }
+            }
+        })
+        .debounce_on(Duration::from_millis(40), timers.clone())
+        .map_sync(move |value| (value, thread_name()))
+        .to_vec()
+        .await;
+
+        let ticks = interval_flow_on(Duration::from_millis(5), TimingOptions::on(timers))
+            .take(3)
+            .map_sync(move |tick| (tick, thread_name()))
+            .to_vec()
+            .await;
+        (debounced, ticks)
+    });
+
+    // The pinned section runs on the pool; values reach the caller's thread
+    let caller = thread_name();
+    assert_eq!(
+        *upstream_threads.lock().unwrap(),
+        vec![Some("timer-pool".to_string()); 6]
+    );
+    assert_eq!(debounced, vec![(2, caller.clone()), (5, caller.clone())]);
+    assert_eq!(
+        ticks,
+        (0..3)
+            .map(|tick| (tick, caller.clone()))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn flat_map_concat_keeps_upstream_order_with_staggered_inners() {
+    let values = flow(|collector| async move {
+        for value in 1..=3u64 {
+            collector.emit(value).await;
+        }
+    })
+    .flat_map_concat(|value| async move {
+        flow(move |collector| async move {
+            // Earlier inner flows are slower
+            sleep(Duration::from_millis(40 - value * 10)).await;
+            collector.emit(value * 10).await;
+            sleep(Duration::from_millis(40 - value * 10)).await;
+            collector.emit(value * 10 + 1).await;
+        })
+    })
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![10, 11, 20, 21, 30, 31]);
+}
+
+#[tokio::test]
+async fn switch_map_aborts_slow_inner_flow_on_new_value() {
+    let values = flow(|collector| async move {
+        collector.emit(1).await;
+        sleep(Duration::from_millis(50)).await;
+        collector.emit(2).await;
+    })
+    .switch_map(|value| async move {
+        flow(move |collector| async move {
+            collector.emit(value * 10).await;
+            sleep(Duration::from_millis(100)).await;
+            collector.emit(value * 10 + 1).await;
+        })
+    })
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![10, 20, 21]);
+}
+
+#[tokio::test]
+async fn flat_map_latest_completes_only_the_last_inner_flow() {
+    let values = flow(|collector| async move {
+        for value in 0..5 {
+            collector.emit(value).await;
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .flat_map_latest_sync(|value| {
+        flow(move |collector| async move {
+            collector.emit(value * 100).await;
+            sleep(Duration::from_millis(100)).await;
+            collector.emit(value * 100 + 1).await;
+        })
+    })
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![0, 100, 200, 300, 400, 401]);
+}
+
+fn fast_source() -> coroflow::Flow<u32> {
+    flow(|collector| async move {
+        for value in 0..50 {
+            collector.emit(value).await;
+            sleep(Duration::from_millis(1)).await;
+        }
+    })
+}
+
+#[tokio::test]
+async fn on_backpressure_drop_discards_values_for_slow_collector() {
+    let (values, dropped) = fast_source().on_backpressure_drop();
+    let received = values
+        .on_each_async(|_| async { sleep(Duration::from_millis(10)).await })
+        .to_vec()
+        .await;
+
+    assert!(dropped.get() > 0);
+    assert!(!received.is_empty());
+    assert_eq!(received.len() + dropped.get(), 50);
+    assert!(received.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[tokio::test]
+async fn on_backpressure_latest_keeps_newest_value() {
+    let (values, dropped) = fast_source().on_backpressure_latest();
+    let received = values
+        .on_each_async(|_| async { sleep(Duration::from_millis(10)).await })
+        .to_vec()
+        .await;
+
+    assert!(dropped.get() > 0);
+    assert_eq!(received.len() + dropped.get(), 50);
+    assert_eq!(received.last(), Some(&49));
+}
+
+#[tokio::test]
+async fn buffer_with_drop_oldest_reports_each_dropped_value() {
+    let dropped = Arc::new(Mutex::new(Vec::new()));
+    let hook = Arc::clone(&dropped);
+    let received = fast_source()
+        .buffer_with(
+            4,
+            BufferOverflow::DropOldest,
+            Some(Arc::new(move |value: &u32| {
+                hook.lock().unwrap().push(*value)
+            })),
+        )
+        .on_each_async(|_| async { sleep(Duration::from_millis(10)).await })
+        .to_vec()
+        .await;
+
+    let dropped = dropped.lock().unwrap().clone();
+    assert!(!dropped.is_empty());
+    assert!(dropped.windows(2).all(|pair| pair[0] < pair[1]));
+    let mut seen: Vec<u32> = received.iter().chain(&dropped).copied().collect();
+    seen.sort_unstable();
+    assert_eq!(seen, (0..50).collect::<Vec<_>>());
+    assert_eq!(received[received.len() - 4..], [46, 47, 48, 49]);
+}
+
+#[tokio::test]
+async fn timestamp_is_monotonic_and_time_interval_tracks_gaps() {
+    let source = flow(|collector| async move {
+        for value in 0..3 {
+            collector.emit(value).await;
+            sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    let stamped = source.clone().timestamp().to_vec().await;
+    assert_eq!(stamped.len(), 3);
+    assert!(stamped.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    assert_eq!(
+        stamped.iter().map(|(_, v)| *v).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+
+    let gaps = source.time_interval().to_vec().await;
+    assert!(gaps[1..]
+        .iter()
+        .all(|(gap, _)| *gap >= Duration::from_millis(10)));
+}
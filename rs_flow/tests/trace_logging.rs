@@ -0,0 +1,49 @@
+#![cfg(feature = "log")]
+
+use coroflow::{flow, FlowExt, FlowTerminal};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+
+struct CapturingLogger {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "coroflow::trace"
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            assert_eq!(record.level(), Level::Debug);
+            self.lines.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger {
+    lines: Mutex::new(Vec::new()),
+};
+
+#[tokio::test]
+async fn trace_logs_each_emission_with_label() {
+    log::set_logger(&LOGGER).expect("logger should only be installed once");
+    log::set_max_level(LevelFilter::Debug);
+
+    let values = flow(|collector| async move {
+        for value in 1..=3 {
+            collector.emit(value).await;
+        }
+    })
+    .trace("numbers")
+    .to_vec()
+    .await;
+
+    assert_eq!(values, vec![1, 2, 3]);
+    assert_eq!(
+        *LOGGER.lines.lock().unwrap(),
+        vec!["[numbers] 1", "[numbers] 2", "[numbers] 3"]
+    );
+}